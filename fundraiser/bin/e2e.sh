@@ -0,0 +1,142 @@
+#!/usr/bin/env bash
+# End-to-end tests of the deployed contract. Needs the node from
+# substrate-contracts-node.sh running and cargo-contract 1.x.
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+URL=${URL:-ws://127.0.0.1:9944}
+ALICE=5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY
+BOB=5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty
+CHARLIE=5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y
+DAVE=5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy
+GOAL=1000000
+DEADLINE=1000000
+METADATA='CampaignMetadata { title: "e2e", description: "", content_hash: [], category: Other }'
+
+# Instantiate the contract of `manifest` with `value` and the constructor arguments
+# that follow, and print its address.
+deploy() {
+    local manifest=$1 value=$2
+    shift 2
+    local args=()
+    if [ $# -gt 0 ]; then
+        args=(--args "$@")
+    fi
+    cargo +nightly contract instantiate --manifest-path "$manifest" --url "$URL" --suri //Alice \
+        --constructor new "${args[@]}" --value "$value" \
+        --salt "$(od -An -N8 -tx1 /dev/urandom | tr -d ' \n')" --skip-confirm \
+        | awk '$1 == "Contract" { address = $2 } END { print address }'
+}
+
+# Submit `message` to the contract of `manifest` at `address` as `suri`, paying
+# `value`, with the message arguments that follow. Only dry-runs it if `DRY_RUN`
+# is set.
+call() {
+    local manifest=$1 address=$2 suri=$3 value=$4 message=$5
+    shift 5
+    local args=()
+    if [ $# -gt 0 ]; then
+        args=(--args "$@")
+    fi
+    cargo +nightly contract call --manifest-path "$manifest" --url "$URL" --suri "$suri" \
+        --contract "$address" --message "$message" "${args[@]}" --value "$value" \
+        ${DRY_RUN:+--dry-run} --skip-confirm
+}
+
+# Dry-run `message` like `call` does and print what it returned.
+query() {
+    DRY_RUN=1 call "$@" | awk '$1 == "Data" { $1 = ""; print substr($0, 2) }'
+}
+
+# Fail unless the dry-run of the `call` arguments that follow returns `expected`.
+expect() {
+    local expected=$1
+    shift
+    local actual
+    actual=$(query "$@")
+    if [ "$actual" != "$expected" ]; then
+        echo "FAIL: $5 returned $actual, expected $expected" >&2
+        exit 1
+    fi
+}
+
+# Create, fund, overfund and withdraw a campaign in the native balance.
+native_flows() {
+    local fundraiser=Cargo.toml
+    local contract
+    # The endowment keeps the contract account alive once every escrow is paid out.
+    contract=$(deploy $fundraiser 1000000000 '"fundraiser"' $ALICE 0 $ALICE)
+    echo "fundraiser at $contract"
+
+    call $fundraiser "$contract" //Bob 0 create_a_funding \
+        $GOAL $DEADLINE Reject AllOrNothing "$METADATA" $BOB None
+    expect "Some(0)" $fundraiser "$contract" //Bob 0 get_funding_status 0
+    expect "Some(Open)" $fundraiser "$contract" //Bob 0 get_campaign_state 0
+
+    call $fundraiser "$contract" //Charlie 600000 fund 0
+    expect "Err(GoalExceeded)" $fundraiser "$contract" //Dave 500000 fund 0
+    call $fundraiser "$contract" //Dave 400000 fund 0
+    expect "Some($GOAL)" $fundraiser "$contract" //Bob 0 get_funding_status 0
+    expect "600000" $fundraiser "$contract" //Bob 0 get_contribution 0 $CHARLIE
+    expect "Some(Succeeded)" $fundraiser "$contract" //Bob 0 get_campaign_state 0
+    expect "Err(GoalAlreadyReached)" $fundraiser "$contract" //Dave 1 fund 0
+
+    expect "Err(NotCampaignOwner)" $fundraiser "$contract" //Charlie 0 withdraw 0
+    call $fundraiser "$contract" //Bob 0 withdraw 0
+    expect "Some(Withdrawn)" $fundraiser "$contract" //Bob 0 get_campaign_state 0
+    expect "0" $fundraiser "$contract" //Bob 0 total_escrowed
+    expect "Err(AlreadyWithdrawn)" $fundraiser "$contract" //Bob 0 withdraw 0
+
+    expect "None" $fundraiser "$contract" //Bob 0 get_funding_status 7
+    expect "Err(UnknownCampaign)" $fundraiser "$contract" //Charlie 1 fund 7
+    expect "Err(UnknownCampaign)" $fundraiser "$contract" //Bob 0 withdraw 7
+}
+
+# Fund, withdraw and refund campaigns in a PSP22 token deployed from psp22_mock.
+token_flows() {
+    local fundraiser=Cargo.toml token=psp22_mock/Cargo.toml
+    local contract psp22
+    contract=$(deploy $fundraiser 1000000000 '"fundraiser"' $ALICE 0 $ALICE)
+    psp22=$(deploy $token 0)
+    echo "fundraiser at $contract, token at $psp22"
+    call $token "$psp22" //Alice 0 mint $CHARLIE $GOAL
+    call $token "$psp22" //Alice 0 mint $DAVE $GOAL
+
+    call $fundraiser "$contract" //Bob 0 create_a_funding \
+        $GOAL $DEADLINE Reject AllOrNothing "$METADATA" $BOB "Some($psp22)"
+    expect "Err(WrongCurrency)" $fundraiser "$contract" //Charlie 1 fund 0
+
+    # Tokens are only pulled in up to what the backer approved.
+    call $token "$psp22" //Charlie 0 approve "$contract" 600000
+    expect "Err(TokenTransferFailed)" $fundraiser "$contract" //Charlie 0 fund_with_token 0 700000
+    call $fundraiser "$contract" //Charlie 0 fund_with_token 0 600000
+    call $token "$psp22" //Dave 0 approve "$contract" 400000
+    call $fundraiser "$contract" //Dave 0 fund_with_token 0 400000
+    expect "Some($GOAL)" $fundraiser "$contract" //Bob 0 get_funding_status 0
+    expect "$GOAL" $token "$psp22" //Bob 0 balance_of "$contract"
+    expect "400000" $token "$psp22" //Bob 0 balance_of $CHARLIE
+    expect "0" $token "$psp22" //Bob 0 balance_of $DAVE
+
+    call $fundraiser "$contract" //Bob 0 withdraw 0
+    expect "Some(Withdrawn)" $fundraiser "$contract" //Bob 0 get_campaign_state 0
+    expect "$GOAL" $token "$psp22" //Bob 0 balance_of $BOB
+    expect "0" $token "$psp22" //Bob 0 balance_of "$contract"
+
+    # A cancelled campaign refunds its backers in the token.
+    call $fundraiser "$contract" //Bob 0 create_a_funding \
+        $GOAL $DEADLINE Reject AllOrNothing "$METADATA" $BOB "Some($psp22)"
+    call $token "$psp22" //Charlie 0 approve "$contract" 300000
+    call $fundraiser "$contract" //Charlie 0 fund_with_token 1 300000
+    expect "100000" $token "$psp22" //Bob 0 balance_of $CHARLIE
+    call $fundraiser "$contract" //Bob 0 cancel_funding 1
+    call $fundraiser "$contract" //Charlie 0 refund 1
+    expect "0" $fundraiser "$contract" //Bob 0 get_contribution 1 $CHARLIE
+    expect "400000" $token "$psp22" //Bob 0 balance_of $CHARLIE
+    expect "0" $token "$psp22" //Bob 0 balance_of "$contract"
+}
+
+cargo +nightly contract build
+cargo +nightly contract build --manifest-path psp22_mock/Cargo.toml
+native_flows
+token_flows
+echo "all e2e tests passed"
@@ -16,20 +16,266 @@ mod fundraiser {
     use ink_prelude::vec::Vec;
 
     type FundingId = u32;
-    const WRONG_TRANSACTION_ID: &str =
-        "The user specified an invalid transaction id. Abort.";
+    type PoolId = u32;
+    type RoundId = u32;
+    type ReceiptId = u64;
+
+    /// The most campaigns a single listing query returns.
+    const MAX_PAGE_SIZE: u32 = 50;
+    /// The highest platform fee the owner can set, in basis points.
+    const MAX_FEE_BPS: u16 = 1_000;
+    /// The number of basis points in a whole.
+    const BPS_DENOMINATOR: Balance = 10_000;
+    /// The longest campaign title, in bytes.
+    const MAX_TITLE_LEN: usize = 64;
+    /// The longest campaign description, in bytes.
+    const MAX_DESCRIPTION_LEN: usize = 280;
+    /// The longest content hash of a campaign, in bytes. Fits any IPFS CID.
+    const MAX_CONTENT_HASH_LEN: usize = 64;
+    /// The most milestones a campaign can be split into.
+    const MAX_MILESTONES: usize = 10;
+    /// The fewest blocks backers get to vote on a milestone release, about a day
+    /// at six seconds a block.
+    const MIN_VOTING_PERIOD: BlockNumber = 14_400;
+    /// The share of a campaign's contributions that has to approve a milestone
+    /// release, in basis points.
+    const MILESTONE_QUORUM_BPS: Balance = 2_000;
+    /// The most reward tiers a campaign can offer.
+    const MAX_REWARD_TIERS: usize = 20;
+    /// The most stretch goals a campaign can set past its goal.
+    const MAX_STRETCH_GOALS: usize = 10;
+    /// The storage layout version this code reads and writes.
+    ///
+    /// * `1` - the first release, which stored no version, only kept the goal of each
+    ///   campaign and left the funding of withdrawn campaigns behind.
+    /// * `2` - campaigns are kept in `transactions` with their full settings, indexed
+    ///   by creator, next to the escrow totals.
+    const STORAGE_VERSION: u32 = 2;
+    /// The most campaigns a sponsor pool can be attached to.
+    const MAX_POOL_CAMPAIGNS: usize = 20;
+    /// The most campaigns a quadratic funding round can take part in.
+    const MAX_ROUND_CAMPAIGNS: usize = 20;
+    /// The fixed-point scale square roots of contributions are taken in.
+    const SQRT_SCALE: Balance = 1_000;
+    /// The most accounts a single allowlist update takes.
+    const MAX_ALLOWLIST_BATCH: usize = 50;
+    /// The most campaigns a single `migrate` call converts.
+    const MIGRATION_BATCH_SIZE: u32 = 50;
+    /// The selector of `PSP22::transfer`.
+    const PSP22_TRANSFER_SELECTOR: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+    /// The selector of `PSP22::transfer_from`.
+    const PSP22_TRANSFER_FROM_SELECTOR: [u8; 4] = [0x54, 0xb3, 0xc7, 0x6e];
 
     /// Errors that can occur upon calling this contract.
     #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
     pub enum Error {
-        /// Returned if the call failed.
-        TransactionFailed,
+        /// Returned if no campaign exists for the given id.
+        UnknownCampaign,
+        /// Returned if the caller is not the creator of the campaign.
+        NotCampaignOwner,
+        /// Returned if the caller is not the proposed new beneficiary of the campaign.
+        NotPendingBeneficiary,
+        /// Returned if the campaign already reached its goal.
+        GoalAlreadyReached,
+        /// Returned if the campaign did not reach its goal yet.
+        GoalNotReached,
+        /// Returned if the contribution would push a campaign that rejects overfunding past its
+        /// goal, or past its hard cap if it has stretch goals.
+        GoalExceeded,
+        /// Returned if the deadline of the campaign has passed.
+        DeadlinePassed,
+        /// Returned if a new campaign is created with a deadline in the past.
+        InvalidDeadline,
+        /// Returned if the campaign was already withdrawn.
+        AlreadyWithdrawn,
+        /// Returned if the campaign was cancelled.
+        CampaignCancelled,
+        /// Returned if the campaign neither failed nor was cancelled.
+        NotRefundable,
+        /// Returned if the caller has no contribution to the campaign.
+        NothingToRefund,
+        /// Returned if the contract holds less than it is asked to pay out.
+        InsufficientContractBalance,
+        /// Returned if a campaign's escrow holds less than it is asked to pay out.
+        InsufficientEscrow,
+        /// Returned if a transfer out of the contract failed.
+        TransferFailed,
+        /// Returned if no more campaign ids can be handed out.
+        IdsExhausted,
+        /// Returned if a balance computation overflowed.
+        Overflow,
+        /// Returned if the caller is not the owner of the contract.
+        NotOwner,
+        /// Returned if the caller is not the proposed new owner of the contract.
+        NotPendingOwner,
+        /// Returned if the contract is paused.
+        ContractPaused,
+        /// Returned if the platform fee is set above `MAX_FEE_BPS`.
+        FeeTooHigh,
+        /// Returned if a campaign title is empty or longer than `MAX_TITLE_LEN`.
+        InvalidTitle,
+        /// Returned if a campaign description is longer than `MAX_DESCRIPTION_LEN`.
+        DescriptionTooLong,
+        /// Returned if a content hash is longer than `MAX_CONTENT_HASH_LEN`.
+        ContentHashTooLong,
+        /// Returned if milestones do not add up to the goal, are not ordered by
+        /// deadline, end before the campaign or are more than `MAX_MILESTONES`.
+        InvalidMilestones,
+        /// Returned if milestones are changed after the campaign received funding.
+        MilestonesLocked,
+        /// Returned if a campaign that pays out in milestones is withdrawn at once.
+        PaysOutInMilestones,
+        /// Returned if the campaign has no milestone left to release.
+        NoPendingMilestone,
+        /// Returned if the release of the current milestone was already requested.
+        ReleaseAlreadyRequested,
+        /// Returned if the deadline of the current milestone has passed.
+        MilestoneDeadlinePassed,
+        /// Returned if no vote is running on the current milestone.
+        NoVoteRunning,
+        /// Returned if the vote on the current milestone is still running.
+        VotingOpen,
+        /// Returned if the caller already voted on the current milestone.
+        AlreadyVoted,
+        /// Returned if the caller has no contribution to the campaign.
+        NotBacker,
+        /// Returned if the campaign already offers `MAX_REWARD_TIERS` reward tiers.
+        TooManyTiers,
+        /// Returned if the campaign has no reward tier with the given id.
+        UnknownTier,
+        /// Returned if the pledge is below the minimum of the reward tier.
+        PledgeBelowTierMinimum,
+        /// Returned if every slot of the reward tier is taken.
+        TierSoldOut,
+        /// Returned if the caller already claimed a reward tier of the campaign.
+        TierAlreadyClaimed,
+        /// Returned if the feature is not available for the campaign's funding model.
+        UnsupportedFundingModel,
+        /// Returned if a campaign is paid in another currency than it is denominated in.
+        WrongCurrency,
+        /// Returned if a PSP22 token refused a transfer.
+        TokenTransferFailed,
+        /// Returned if the new code hash could not be set.
+        UpgradeFailed,
+        /// Returned if the storage has to be migrated before the contract can be used.
+        MigrationPending,
+        /// Returned if the storage is already at `STORAGE_VERSION`.
+        NothingToMigrate,
+        /// Returned if the cancellation penalty is above the whole deposit.
+        PenaltyTooHigh,
+        /// Returned if the campaign has no deposit left to pay back.
+        NoDeposit,
+        /// Returned if the campaign is still open.
+        CampaignOpen,
+        /// Returned if nothing is paid in.
+        ZeroContribution,
+        /// Returned if a payment is below the campaign's minimum contribution.
+        ContributionTooSmall,
+        /// Returned if a backer's total would go past the campaign's per-backer cap.
+        BackerCapExceeded,
+        /// Returned if the campaign already has as many backers as it allows.
+        TooManyBackers,
+        /// Returned if the minimum contribution is above the per-backer cap.
+        InvalidLimits,
+        /// Returned if the caller may not back a private campaign.
+        NotAllowed,
+        /// Returned if a Merkle proof does not lead to the campaign's root.
+        InvalidProof,
+        /// Returned if more accounts are passed than `MAX_ALLOWLIST_BATCH`.
+        BatchTooLarge,
+        /// Returned if the sponsor pool does not exist.
+        UnknownPool,
+        /// Returned if the caller is not the sponsor of the pool.
+        NotSponsor,
+        /// Returned if a pool would match nothing.
+        InvalidMatchRatio,
+        /// Returned if the campaign is already matched by a pool.
+        PoolAlreadyAttached,
+        /// Returned if the pool is attached to `MAX_POOL_CAMPAIGNS` campaigns already.
+        TooManyPoolCampaigns,
+        /// Returned if a campaign the pool or round matches still takes funding.
+        PoolCampaignsOpen,
+        /// Returned if the pool has nothing left to pay back.
+        NothingToReclaim,
+        /// Returned if the quadratic funding round does not exist.
+        UnknownRound,
+        /// Returned if the round ends before it starts or has already ended.
+        InvalidRound,
+        /// Returned if the caller is not the operator of the round.
+        NotRoundOperator,
+        /// Returned if the round has already ended.
+        RoundEnded,
+        /// Returned if the round has not ended yet.
+        RoundNotEnded,
+        /// Returned if the round was already finalized.
+        RoundFinalized,
+        /// Returned if the round was not finalized yet.
+        RoundNotFinalized,
+        /// Returned if the campaign already takes part in a round.
+        AlreadyInRound,
+        /// Returned if the round has `MAX_ROUND_CAMPAIGNS` campaigns already.
+        TooManyRoundCampaigns,
+        /// Returned if stretch goals are not ascending, not above the goal or past the hard cap.
+        InvalidStretchGoals,
+        /// Returned if the campaign raised as much as its hard cap allows.
+        HardCapReached,
+        /// Returned if the campaign does not let backers retract their contributions.
+        RetractionDisabled,
+        /// Returned if the campaign's retraction window has closed.
+        RetractionClosed,
+        /// Returned if a backer retracts more than they contributed.
+        AmountExceedsContribution,
+        /// Returned if the campaign was carried over from the first release, which
+        /// kept no record of its backers, so it can not be cancelled.
+        LegacyCampaign,
+    }
+
+    /// The contract's result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Errors a PSP22 token returns, as laid out by the standard.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    /// Errors a PSP34 token returns, as laid out by the standard.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum PSP34Error {
+        Custom(String),
+        SelfApprove,
+        NotApproved,
+        TokenExists,
+        TokenNotExists,
+        SafeTransferCheckFailed(String),
+    }
+
+    /// The id of a PSP34 token, as laid out by the standard. Receipts use `U64`.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum Id {
+        U8(u8),
+        U16(u16),
+        U32(u32),
+        U64(u64),
+        U128(u128),
+        Bytes(Vec<u8>),
     }
 
     /// Defines the storage of your contract.
-    /// Add new fields to the below struct in order
-    /// to add new static storage fields to your contract.
+    ///
+    /// The fields up to and including `current_funding` are the layout of the first
+    /// release and must keep their place. Every field after them is a `Mapping`,
+    /// which takes up a single key and is not read when the contract is loaded, so
+    /// new state is only ever added as another `Mapping` at the end.
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct Fundraiser {
@@ -37,12 +283,113 @@ mod fundraiser {
         name: String,
         owner: AccountId,
         transaction_list: Transactions,
+        /// Map the transaction id to the campaign as the first release stored it.
+        ///
+        /// Emptied by `migrate`.
+        legacy_transactions: Mapping<FundingId, LegacyTransaction>,
+        /// Map the transaction id to the balance held in escrow for it.
+        current_funding: Mapping<FundingId, Balance>,
+        /// The storage layout version the stored state is in, `1` if none is stored.
+        version: Mapping<(), u32>,
+        /// The first campaign a running migration has not converted yet.
+        migration_cursor: Mapping<(), FundingId>,
         /// Map the transaction id to its unexecuted transaction.
         transactions: Mapping<FundingId, Transaction>,
-        current_funding: Mapping<FundingId, Balance>,
+        /// Map a (creator, index) pair to the id of the creator's index-th campaign.
+        by_creator: Mapping<(AccountId, u32), FundingId>,
+        /// Map a creator to the number of campaigns they created.
+        creator_count: Mapping<AccountId, u32>,
+        /// Map a currency, `None` for the native balance, to the sum of all campaign
+        /// escrows in it. The native sum never exceeds the contract balance.
+        total_escrowed: Mapping<Option<AccountId>, Balance>,
+        /// Map a (transaction id, backer) pair to the amount the backer paid in.
+        contributions: Mapping<(FundingId, AccountId), Balance>,
+        /// Map the transaction id to the sum of its backers' contributions.
+        total_contributions: Mapping<FundingId, Balance>,
+        /// Map the transaction id to the milestones its funding is released in.
+        milestones: Mapping<FundingId, Vec<Milestone>>,
+        /// Map a (transaction id, milestone index, backer) triple to whether the backer voted.
+        milestone_votes: Mapping<(FundingId, u32, AccountId), bool>,
+        /// Map the transaction id to the reward tiers it offers.
+        reward_tiers: Mapping<FundingId, Vec<RewardTier>>,
+        /// Map a (transaction id, backer) pair to the reward tier the backer claimed.
+        claimed_tiers: Mapping<(FundingId, AccountId), u32>,
+        /// Map the transaction id to the limits its contributions have to keep to.
+        contribution_limits: Mapping<FundingId, ContributionLimits>,
+        /// Map the transaction id to the number of backers with a contribution in it.
+        backer_count: Mapping<FundingId, u32>,
+        /// Map the transaction id to who may back it.
+        access: Mapping<FundingId, CampaignAccess>,
+        /// Map a (transaction id, account) pair to whether the account may back a private campaign.
+        allowlist: Mapping<(FundingId, AccountId), bool>,
+        /// Map the pool id to its sponsor pool.
+        sponsor_pools: Mapping<PoolId, SponsorPool>,
+        /// The id the next sponsor pool is created with.
+        next_pool_id: Mapping<(), PoolId>,
+        /// Map the transaction id to the pool that matches its contributions.
+        campaign_pools: Mapping<FundingId, PoolId>,
+        /// Map the transaction id to the part of its escrow its pool paid in.
+        pool_matches: Mapping<FundingId, Balance>,
+        /// Map a (transaction id, backer) pair to the part of the pool match the
+        /// backer's contributions earned.
+        backer_matches: Mapping<(FundingId, AccountId), Balance>,
+        /// Map the transaction id to whether and until when backers may retract.
+        retraction_policies: Mapping<FundingId, RetractionPolicy>,
+        /// Map the transaction id to the stretch goals it sets past its goal.
+        stretch_goals: Mapping<FundingId, Vec<StretchGoal>>,
+        /// Map the transaction id to the most it raises while going for its stretch goals.
+        hard_caps: Mapping<FundingId, Balance>,
+        /// Map the receipt id to what the receipt records.
+        receipts: Mapping<ReceiptId, Receipt>,
+        /// Map the receipt id to the account holding it.
+        receipt_owners: Mapping<ReceiptId, AccountId>,
+        /// Map an account to the number of receipts it holds.
+        receipt_balances: Mapping<AccountId, u32>,
+        /// Map a (transaction id, backer) pair to the receipt of the backer's contribution.
+        backer_receipts: Mapping<(FundingId, AccountId), ReceiptId>,
+        /// Map an (owner, operator, receipt) triple to whether the operator may transfer
+        /// the receipt, or any receipt of the owner if none is given.
+        receipt_approvals: Mapping<(AccountId, AccountId, Option<ReceiptId>), bool>,
+        /// The id the next receipt is minted with.
+        next_receipt_id: Mapping<(), ReceiptId>,
+        /// The number of receipts in existence.
+        receipt_supply: Mapping<(), Balance>,
+        /// Map the round id to its quadratic funding round.
+        rounds: Mapping<RoundId, Round>,
+        /// The id the next round is created with.
+        next_round_id: Mapping<(), RoundId>,
+        /// Map the transaction id to the round it takes part in.
+        campaign_rounds: Mapping<FundingId, RoundId>,
+        /// Map a (transaction id, backer) pair to what the backer contributed while
+        /// the campaign's round was running.
+        round_contributions: Mapping<(FundingId, AccountId), Balance>,
+        /// Map the transaction id to the sum of the square roots of its round
+        /// contributions, scaled by `SQRT_SCALE`.
+        round_sqrt_sums: Mapping<FundingId, Balance>,
+        /// Map the transaction id to the part of its escrow its round paid in.
+        round_matches: Mapping<FundingId, Balance>,
+        /// While set, no campaigns can be created, funded or withdrawn.
+        paused: Mapping<(), bool>,
+        /// The account `owner` proposed to hand the contract over to.
+        pending_owner: Mapping<(), AccountId>,
+        /// The platform fee new campaigns are created with, in basis points.
+        fee_bps: Mapping<(), u16>,
+        /// The account platform fees are paid to, the `owner` if none is set.
+        treasury: Mapping<(), AccountId>,
+        /// The share of the creator's deposit new campaigns forfeit to the `treasury`
+        /// when the creator cancels them, in basis points.
+        cancellation_penalty_bps: Mapping<(), u16>,
+        /// Map the transaction id to the deposit its creator put down.
+        deposits: Mapping<FundingId, Deposit>,
+        /// Map the transaction id to the number of blocks backers vote on each of
+        /// its milestone releases.
+        voting_periods: Mapping<FundingId, BlockNumber>,
+        /// Map the transaction id to whether `migrate` carried it over from the
+        /// first release.
+        legacy_campaigns: Mapping<FundingId, bool>,
     }
 
-    #[derive(SpreadLayout, SpreadAllocate, Default)]
+    #[derive(Debug, SpreadLayout, SpreadAllocate, Default)]
     #[cfg_attr(
         feature = "std",
         derive(
@@ -51,6 +398,10 @@ mod fundraiser {
     )]
     pub struct Transactions {
         /// Just store all transaction ids packed.
+        ///
+        /// Only the first release filled this in, `migrate` empties it. Every id
+        /// below `next_id` is a campaign, so only the per-creator listing needs
+        /// an index.
         transactions: Vec<FundingId>,
         /// We just increment this whenever a new transaction is created.
         /// We never decrement or defragment. For now, the contract becomes defunct
@@ -58,9 +409,28 @@ mod fundraiser {
         next_id: FundingId
     }
 
+    /// A campaign as the first release stored it in `legacy_transactions`.
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct LegacyTransaction {
+        /// The account that created the campaign and withdraws its funding.
+        pub callee: AccountId,
+        /// The amount of chain balance the campaign asked for.
+        pub expected_value: Balance,
+    }
+
     /// A Transaction is what every `owner` can submit for confirmation by other owners.
     /// If enough owners agree it will be executed by the contract.
-    #[derive(scale::Encode, scale::Decode, SpreadLayout, SpreadAllocate, PackedLayout)]
+    ///
+    /// Campaigns are stored packed, so a new field changes how every stored one
+    /// decodes. Further state of a campaign goes into a `Mapping` of its own.
+    #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
     #[cfg_attr(
         feature = "std",
         derive(
@@ -73,137 +443,5206 @@ mod fundraiser {
         pub callee: AccountId,
         /// The amount of chain balance that is transferred to the callee.
         pub expected_value: Balance,
+        /// The account the funding is paid out to.
+        pub beneficiary: AccountId,
+        /// The PSP22 token the campaign is denominated in, or `None` for the native balance.
+        pub currency: Option<AccountId>,
+        /// The account the callee proposed to hand the payout over to.
+        pub pending_beneficiary: Option<AccountId>,
+        /// The last block in which the campaign accepts funding.
+        pub deadline: BlockNumber,
+        /// What happens to contributions that go past `expected_value`.
+        pub overflow_policy: OverflowPolicy,
+        /// Whether the campaign has to reach its goal to be paid out.
+        pub funding_model: FundingModel,
+        /// The platform fee in effect when the campaign was created, in basis points.
+        pub fee_bps: u16,
+        /// What the campaign is about.
+        pub metadata: CampaignMetadata,
+        /// The part of the funding already paid out through milestones.
+        pub released: Balance,
+        /// Whether the callee already withdrew the funding.
+        pub withdrawn: bool,
+        /// Whether the campaign was called off before it could complete.
+        pub cancelled: bool,
     }
 
-    impl Fundraiser {
-        //// Constructor that initializes the `bool` value to the given `init_value`.
-        #[ink(constructor)]
-        pub fn new(name_value: String, owner_value: AccountId) -> Self {
-            // This call is required in order to correctly initialize the
-            // `Mapping`s of our contract.
-            ink_lang::utils::initialize_contract(|contract: &mut Self| {
-                contract.name = name_value;
-                contract.owner = owner_value;
-                contract.transactions = Default::default();
-            })
-        }
-        
-        /// Add a new transaction candidate to the contract.
-        ///
-        /// This also confirms the transaction for the caller. This can be called by any owner.
-        #[ink(message)]
-        pub fn create_a_funding(
-            &mut self,
-            expected_value: Balance,
-        ) -> FundingId {
-            
-            // Generate transaction id for the next submit request
-            let fund_id = self.transaction_list.next_id;
-            self.transaction_list.next_id = fund_id.checked_add(1).expect("Transaction ids exhausted.");
-            
-            // Create new transaction object
-            let transaction = Transaction {
-                callee: self.env().caller(),
-                expected_value: expected_value,
-            };
-            self.transactions.insert(fund_id, &transaction);
-            self.transaction_list.transactions.push(fund_id);
+    /// The native balance a creator put down when creating a campaign.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Deposit {
+        /// The part of the deposit not yet paid back or forfeited.
+        pub amount: Balance,
+        /// The cancellation penalty in effect when the campaign was created, in basis points.
+        pub penalty_bps: u16,
+    }
 
-            fund_id
-        }
+    /// A tranche of a campaign's funding that backers have to approve before it is paid out.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Milestone {
+        /// The amount released when the milestone is approved. The last milestone
+        /// releases whatever is left in escrow.
+        pub amount: Balance,
+        /// The last block in which the creator can ask for the release.
+        pub deadline: BlockNumber,
+        /// Where the milestone is in its release.
+        pub status: MilestoneStatus,
+        /// The last block of the vote on the release.
+        pub voting_ends: BlockNumber,
+        /// The contributions of the backers who approved the release.
+        pub approvals: Balance,
+        /// The contributions of the backers who rejected the release.
+        pub rejections: Balance,
+    }
 
-        /// Read transaction by its id
-        #[ink(message)]
-        pub fn get_funding(&self, fund_id: FundingId) -> Option<Transaction> {
-            let transaction = self.transactions.get(&fund_id);
-            transaction
-        }
+    /// Where a milestone is in its release.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum MilestoneStatus {
+        /// The creator has not asked for the release yet.
+        Pending,
+        /// Backers are voting on the release.
+        Voting,
+        /// The tranche was paid out.
+        Released,
+        /// Backers rejected the release, or it was not asked for in time.
+        Rejected,
+    }
 
-        /// Read transaction current funding
-        #[ink(message)]
-        pub fn get_funding_status(&self, fund_id: FundingId) -> Option<Balance> {
-            self.current_funding.get(&fund_id)
-        }
+    /// Whether backers may take back their contributions while a campaign is open.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum RetractionPolicy {
+        /// Up to the deadline.
+        #[default]
+        Allowed,
+        /// Never.
+        Disabled,
+        /// Up to the given number of blocks before the deadline.
+        CutOff(BlockNumber),
+    }
 
-        #[ink(message, payable)]
-        pub fn fund(&mut self, fund_id: FundingId) {
-            let caller = self.env().caller();
-            let value = self.env().transferred_value();
+    /// Who may back a campaign.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum CampaignAccess {
+        /// Anyone.
+        #[default]
+        Public,
+        /// Only the accounts the creator put on the allowlist.
+        Allowlist,
+        /// The allowlisted accounts and whoever proves with `fund_with_proof` that
+        /// they are a leaf of the Merkle tree with this root.
+        MerkleRoot(Hash),
+    }
 
-            self.ensure_transaction_exists(fund_id);
-            self.ensure_funding_is_not_exceed(fund_id);
-            let funding = self.current_funding.get(fund_id);
-            let mut f = value;
-            if funding.is_some() {
-                f = funding.unwrap().checked_add(value).expect("Funding exhausted.");
-            }
+    /// Native balance a sponsor put aside to match the contributions to some campaigns.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct SponsorPool {
+        /// The account that paid the pool in and gets back what is left of it.
+        pub sponsor: AccountId,
+        /// The part of the pool not matched yet.
+        pub available: Balance,
+        /// How much is matched per contributed unit, in basis points. `10_000` matches 1:1.
+        pub ratio_bps: u16,
+        /// The most the pool matches per campaign.
+        pub cap_per_campaign: Balance,
+        /// The campaigns the pool matches.
+        pub campaigns: Vec<FundingId>,
+    }
 
-            self.current_funding.remove(fund_id);
-            self.current_funding.insert(fund_id, &f);
-            
-            ink_env::debug_println!("thanks for the funding of {:?} from {:?}", value, caller);
-            ink_env::debug_println!("transaction id {:?} current balance {:?}", fund_id, value);
-        }
+    /// A matching pool split across campaigns by the quadratic funding formula.
+    ///
+    /// Every campaign's share is proportional to the square of the sum of the
+    /// square roots of the contributions it got while the round was running.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Round {
+        /// The account that paid the matching pool in and gets back what is left of it.
+        pub operator: AccountId,
+        /// The native balance split across the campaigns.
+        pub matching_pool: Balance,
+        /// The first block contributions count toward the round.
+        pub start: BlockNumber,
+        /// The last block contributions count toward the round.
+        pub end: BlockNumber,
+        /// The campaigns taking part in the round.
+        pub campaigns: Vec<FundingId>,
+        /// Whether the matching pool was split already.
+        pub finalized: bool,
+    }
 
-        #[ink(message)]
-        pub fn withdraw(&mut self, fund_id: FundingId) {
-            self.ensure_transaction_exists(fund_id);
-            self.ensure_transaction_owner(fund_id, self.env().caller());
-            self.ensure_funding_is_full(fund_id);
+    /// What a backer receipt records about the contribution it was minted for.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct Receipt {
+        /// The campaign that was backed.
+        pub fund_id: FundingId,
+        /// What the backer contributed in total.
+        pub amount: Balance,
+        /// The reward tier the backer claimed, if any.
+        pub tier_id: Option<u32>,
+    }
 
-            let funding = self.current_funding.get(fund_id).unwrap();
-            
-            ink_env::debug_println!("contract balance: {}", self.env().balance());
+    /// A further target a campaign works toward once it reached its goal.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct StretchGoal {
+        /// The funding at which the stretch goal is unlocked.
+        pub amount: Balance,
+        /// The hash of what the creator promises once it is unlocked.
+        pub metadata_hash: Hash,
+    }
 
-            assert!(funding <= self.env().balance(), "insufficient funds!");
+    /// Bounds a campaign puts on its contributions. `None` means unbounded.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct ContributionLimits {
+        /// The smallest payment a single `fund` call may make.
+        pub min_contribution: Option<Balance>,
+        /// The most a single backer may contribute in total.
+        pub max_per_backer: Option<Balance>,
+        /// The most backers the campaign accepts.
+        pub max_backers: Option<u32>,
+    }
 
-            if self.env().transfer(self.env().caller(), funding).is_err() {
-                ink_env::debug_println!("Failed");
-                panic!(
-                    "requested transfer failed. this can be the case if the contract does not\
-                     have sufficient free funds or if the transfer would have brought the\
-                     contract's balance below minimum balance."
-                )
-            }
-            self.transactions.remove(&fund_id);
-        }
+    /// A reward backers get for pledging at least `min_pledge`, while slots last.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct RewardTier {
+        /// The smallest pledge that claims the tier.
+        pub min_pledge: Balance,
+        /// The number of backers that can claim the tier.
+        pub max_slots: u32,
+        /// The number of backers that claimed the tier.
+        pub claimed: u32,
+        /// The hash of the off-chain description of the reward.
+        pub metadata_hash: Hash,
+    }
 
-        #[ink(message)]
-        pub fn current_balance(&self) -> Balance {
-            self.env().balance()
-        }
+    /// Describes a campaign to the people browsing it.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub struct CampaignMetadata {
+        /// A non-empty title of at most `MAX_TITLE_LEN` bytes.
+        pub title: String,
+        /// A description of at most `MAX_DESCRIPTION_LEN` bytes.
+        pub description: String,
+        /// The hash of the campaign's off-chain media, e.g. the bytes of an IPFS CID.
+        pub content_hash: Vec<u8>,
+        /// The category the campaign is listed under.
+        pub category: Category,
+    }
 
-        #[ink(message)]
-        pub fn print(&self) {
-            let caller = self.env().caller();
-            ink_env::debug_println!("got a call from {:?}", caller);
-        }
+    /// The category a campaign is listed under.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum Category {
+        Other,
+        Art,
+        Charity,
+        Community,
+        Education,
+        Health,
+        Technology,
+    }
 
-        /// Panic if the transaction `fund_id` does not exit.
-        fn ensure_transaction_exists(&self, fund_id: FundingId) {
-            self.transactions.get(fund_id).expect(WRONG_TRANSACTION_ID);
-        }
+    /// A short overview of a campaign, as returned by the listing queries.
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub struct CampaignSummary {
+        /// The title of the campaign.
+        pub title: String,
+        /// The category of the campaign.
+        pub category: Category,
+        /// The creator of the campaign.
+        pub creator: AccountId,
+        /// The goal of the campaign.
+        pub expected_value: Balance,
+        /// The balance currently held in escrow for the campaign.
+        pub current_funding: Balance,
+        /// The last block in which the campaign accepts funding.
+        pub deadline: BlockNumber,
+        /// The lifecycle state of the campaign.
+        pub state: CampaignState,
+    }
 
-        /// Panic if the transaction `fund_id` does not exit.
-        fn ensure_transaction_owner(&self, fund_id: FundingId, caller_id: AccountId) {
-            let transaction = self.transactions.get(fund_id).unwrap();
-            assert_eq!(transaction.callee, caller_id);
-        }
+    /// How a campaign treats contributions beyond its `expected_value`.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum OverflowPolicy {
+        /// Contributions that would go past the goal fail.
+        Reject,
+        /// Only the part up to the goal is kept, the rest is sent back to the backer.
+        Refund,
+        /// The campaign keeps accepting funding until its deadline.
+        Accept,
+    }
 
-        /// Panic if the current funding is matched the expected
-        fn ensure_funding_is_not_exceed(&self, fund_id: FundingId) {
-            let transaction = self.transactions.get(fund_id).unwrap();
-            let current_funding = self.current_funding.get(fund_id);
-            if current_funding.is_some() {
-                assert!(current_funding.unwrap() < transaction.expected_value);
-            }
-        }
+    /// Whether a campaign has to reach its goal to be paid out.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo,
+            ink_storage::traits::StorageLayout
+        )
+    )]
+    pub enum FundingModel {
+        /// The funding is only paid out if the goal is reached by the deadline.
+        /// Otherwise backers get their contributions back.
+        AllOrNothing,
+        /// The beneficiary keeps whatever was raised, and can withdraw it while
+        /// the campaign is still open.
+        KeepItAll,
+    }
+
+    /// The lifecycle state of a campaign, derived from its funding and deadline.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum CampaignState {
+        /// The campaign accepts funding.
+        Open,
+        /// The goal was reached and the callee may withdraw.
+        Succeeded,
+        /// The deadline passed before the goal was reached, or backers rejected a
+        /// milestone. Backers may ask for a refund. Keep-it-all campaigns only fail
+        /// if they raised nothing.
+        Failed,
+        /// The callee withdrew the funding.
+        Withdrawn,
+        /// The campaign was called off. Backers may ask for a refund.
+        Cancelled,
+    }
+
+    /// Emitted when a new campaign is created.
+    #[ink(event)]
+    pub struct CampaignCreated {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        creator: AccountId,
+        expected_value: Balance,
+        deadline: BlockNumber,
+    }
+
+    /// Emitted when a backer funds a campaign.
+    #[ink(event)]
+    pub struct Contributed {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        backer: AccountId,
+        amount: Balance,
+        total: Balance,
+    }
+
+    /// Emitted when a sponsor sets a pool aside.
+    #[ink(event)]
+    pub struct SponsorPoolCreated {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        sponsor: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when a sponsor pool starts matching a campaign.
+    #[ink(event)]
+    pub struct PoolAttached {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        fund_id: FundingId,
+    }
+
+    /// Emitted when a sponsor pool matches a contribution.
+    #[ink(event)]
+    pub struct Matched {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        fund_id: FundingId,
+        amount: Balance,
+    }
+
+    /// Emitted when a sponsor takes back what is left of a pool.
+    #[ink(event)]
+    pub struct PoolReclaimed {
+        #[ink(topic)]
+        pool_id: PoolId,
+        amount: Balance,
+    }
+
+    /// Emitted when an operator opens a quadratic funding round.
+    #[ink(event)]
+    pub struct RoundCreated {
+        #[ink(topic)]
+        round_id: RoundId,
+        #[ink(topic)]
+        operator: AccountId,
+        matching_pool: Balance,
+        start: BlockNumber,
+        end: BlockNumber,
+    }
+
+    /// Emitted when a campaign joins a quadratic funding round.
+    #[ink(event)]
+    pub struct RoundJoined {
+        #[ink(topic)]
+        round_id: RoundId,
+        #[ink(topic)]
+        fund_id: FundingId,
+    }
+
+    /// Emitted when a round credits a campaign with its share of the matching pool.
+    #[ink(event)]
+    pub struct RoundMatched {
+        #[ink(topic)]
+        round_id: RoundId,
+        #[ink(topic)]
+        fund_id: FundingId,
+        amount: Balance,
+    }
+
+    /// Emitted when the matching pool of a round was split.
+    #[ink(event)]
+    pub struct RoundFinalized {
+        #[ink(topic)]
+        round_id: RoundId,
+        distributed: Balance,
+    }
+
+    /// Emitted when an operator takes back the matches of campaigns that failed.
+    #[ink(event)]
+    pub struct RoundReclaimed {
+        #[ink(topic)]
+        round_id: RoundId,
+        amount: Balance,
+    }
+
+    /// Emitted when the creator sets or removes the stretch goals of a campaign.
+    #[ink(event)]
+    pub struct StretchGoalsSet {
+        #[ink(topic)]
+        fund_id: FundingId,
+        stretch_goals: u32,
+        /// `None` once the campaign stops at its goal again.
+        hard_cap: Option<Balance>,
+    }
+
+    /// Emitted when a contribution makes a campaign reach one of its stretch goals.
+    #[ink(event)]
+    pub struct StretchGoalUnlocked {
+        #[ink(topic)]
+        fund_id: FundingId,
+        /// The position of the stretch goal, counting from 1.
+        level: u32,
+        amount: Balance,
+    }
+
+    /// Emitted when a contribution makes a campaign reach its goal.
+    #[ink(event)]
+    pub struct GoalReached {
+        #[ink(topic)]
+        fund_id: FundingId,
+        total: Balance,
+    }
+
+    /// Emitted when the creator withdraws the funding of a campaign.
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+        fee: Balance,
+    }
+
+    /// Emitted when a backer is paid back their contribution.
+    #[ink(event)]
+    pub struct Refunded {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        backer: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when the creator changes whether backers may retract from a campaign.
+    #[ink(event)]
+    pub struct RetractionPolicyChanged {
+        #[ink(topic)]
+        fund_id: FundingId,
+        policy: RetractionPolicy,
+    }
+
+    /// Emitted when a backer takes back part of their contribution to an open campaign.
+    #[ink(event)]
+    pub struct Unfunded {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        backer: AccountId,
+        amount: Balance,
+        total: Balance,
+    }
+
+    /// Emitted when a receipt is minted, transferred or burnt, as laid out by PSP34.
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        #[ink(topic)]
+        id: Id,
+    }
+
+    /// Emitted when an owner lets an operator transfer their receipts, as laid out by PSP34.
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        #[ink(topic)]
+        id: Option<Id>,
+        approved: bool,
+    }
+
+    /// Emitted when a campaign is called off.
+    #[ink(event)]
+    pub struct CampaignCancelled {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    /// Emitted when the owner pauses the contract.
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    /// Emitted when the owner unpauses the contract.
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    /// Emitted when the owner proposes to hand the contract over.
+    #[ink(event)]
+    pub struct OwnershipProposed {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        proposed: AccountId,
+    }
+
+    /// Emitted when the proposed owner accepts the contract.
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+    }
+
+    /// Emitted when the creator proposes a new beneficiary for a campaign.
+    #[ink(event)]
+    pub struct BeneficiaryProposed {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        proposed: AccountId,
+    }
+
+    /// Emitted when the proposed beneficiary of a campaign accepts the handover.
+    #[ink(event)]
+    pub struct BeneficiaryChanged {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        beneficiary: AccountId,
+    }
+
+    /// Emitted when the creator changes the metadata of a campaign.
+    #[ink(event)]
+    pub struct MetadataUpdated {
+        #[ink(topic)]
+        fund_id: FundingId,
+    }
+
+    /// Emitted when the creator changes the contribution limits of a campaign.
+    #[ink(event)]
+    pub struct ContributionLimitsUpdated {
+        #[ink(topic)]
+        fund_id: FundingId,
+        limits: ContributionLimits,
+    }
+
+    /// Emitted when the creator changes who may back a campaign.
+    #[ink(event)]
+    pub struct AccessChanged {
+        #[ink(topic)]
+        fund_id: FundingId,
+        access: CampaignAccess,
+    }
+
+    /// Emitted when the creator adds accounts to or removes them from the allowlist
+    /// of a campaign.
+    #[ink(event)]
+    pub struct AllowlistUpdated {
+        #[ink(topic)]
+        fund_id: FundingId,
+        accounts: Vec<AccountId>,
+        allowed: bool,
+    }
+
+    /// Emitted when the creator splits the funding of a campaign into milestones.
+    #[ink(event)]
+    pub struct MilestonesSet {
+        #[ink(topic)]
+        fund_id: FundingId,
+        milestones: u32,
+        voting_period: BlockNumber,
+    }
+
+    /// Emitted when the creator asks backers to approve the release of a milestone.
+    #[ink(event)]
+    pub struct MilestoneReleaseRequested {
+        #[ink(topic)]
+        fund_id: FundingId,
+        milestone: u32,
+        voting_ends: BlockNumber,
+    }
+
+    /// Emitted when a backer votes on the release of a milestone.
+    #[ink(event)]
+    pub struct MilestoneVoted {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        backer: AccountId,
+        milestone: u32,
+        approve: bool,
+        weight: Balance,
+    }
+
+    /// Emitted when the tranche of an approved milestone is paid out.
+    #[ink(event)]
+    pub struct MilestoneReleased {
+        #[ink(topic)]
+        fund_id: FundingId,
+        milestone: u32,
+        amount: Balance,
+        fee: Balance,
+    }
+
+    /// Emitted when a milestone is rejected and backers can reclaim the rest of the funding.
+    #[ink(event)]
+    pub struct MilestoneRejected {
+        #[ink(topic)]
+        fund_id: FundingId,
+        milestone: u32,
+    }
+
+    /// Emitted when the creator adds a reward tier to a campaign.
+    #[ink(event)]
+    pub struct RewardTierAdded {
+        #[ink(topic)]
+        fund_id: FundingId,
+        tier_id: u32,
+        min_pledge: Balance,
+        max_slots: u32,
+    }
+
+    /// Emitted when a backer claims a reward tier.
+    #[ink(event)]
+    pub struct TierClaimed {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        backer: AccountId,
+        tier_id: u32,
+    }
+
+    /// Emitted when the owner changes the platform fee.
+    #[ink(event)]
+    pub struct FeeChanged {
+        fee_bps: u16,
+    }
+
+    /// Emitted when the owner changes the account platform fees are paid to.
+    #[ink(event)]
+    pub struct TreasuryChanged {
+        #[ink(topic)]
+        treasury: AccountId,
+    }
+
+    /// Emitted when the owner changes the cancellation penalty.
+    #[ink(event)]
+    pub struct CancellationPenaltyChanged {
+        penalty_bps: u16,
+    }
+
+    /// Emitted when a creator takes back their deposit.
+    #[ink(event)]
+    pub struct DepositReturned {
+        #[ink(topic)]
+        fund_id: FundingId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+        penalty: Balance,
+    }
+
+    /// Emitted when the owner replaced the code of the contract.
+    #[ink(event)]
+    pub struct Upgraded {
+        code_hash: [u8; 32],
+    }
+
+    /// Emitted when the storage finished migrating to a new layout version.
+    #[ink(event)]
+    pub struct Migrated {
+        from: u32,
+        to: u32,
+    }
+
+    impl Fundraiser {
+        /// Constructor that sets up the platform fee paid to `treasury` on every withdrawal.
+        ///
+        /// Panics if `fee_bps` is above `MAX_FEE_BPS`.
+        #[ink(constructor)]
+        pub fn new(name_value: String, owner_value: AccountId, fee_bps: u16, treasury: AccountId) -> Self {
+            assert!(fee_bps <= MAX_FEE_BPS, "platform fee is too high.");
+            // This call is required in order to correctly initialize the
+            // `Mapping`s of our contract.
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                contract.name = name_value;
+                contract.owner = owner_value;
+                contract.transactions = Default::default();
+                contract.fee_bps.insert((), &fee_bps);
+                contract.treasury.insert((), &treasury);
+                contract.version.insert((), &STORAGE_VERSION);
+            })
+        }
+        
+        /// Add a new transaction candidate to the contract.
+        ///
+        /// The campaign accepts funding up to and including the `deadline` block.
+        /// Contributions past `expected_value` are handled as `overflow_policy` says.
+        /// The funding is paid out to `beneficiary`, who may differ from the caller,
+        /// as `funding_model` says. Campaigns with a `currency` are funded in that
+        /// PSP22 token through `fund_with_token` instead of the native balance.
+        ///
+        /// The transferred value is kept as the creator's deposit. It is paid back
+        /// once the campaign closes, less the cancellation penalty if the creator
+        /// calls the campaign off with `cancel_funding`.
+        #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn create_a_funding(
+            &mut self,
+            expected_value: Balance,
+            deadline: BlockNumber,
+            overflow_policy: OverflowPolicy,
+            funding_model: FundingModel,
+            metadata: CampaignMetadata,
+            beneficiary: AccountId,
+            currency: Option<AccountId>,
+        ) -> Result<FundingId> {
+            self.ensure_not_paused()?;
+            if deadline < self.env().block_number() {
+                return Err(Error::InvalidDeadline)
+            }
+            Self::ensure_valid_metadata(&metadata)?;
+
+            // Generate transaction id for the next submit request
+            let fund_id = self.transaction_list.next_id;
+            self.transaction_list.next_id = fund_id.checked_add(1).ok_or(Error::IdsExhausted)?;
+            
+            // Create new transaction object
+            let caller = self.env().caller();
+            let transaction = Transaction {
+                callee: caller,
+                expected_value: expected_value,
+                beneficiary,
+                pending_beneficiary: None,
+                currency,
+                deadline,
+                overflow_policy,
+                funding_model,
+                fee_bps: self.fee_bps(),
+                metadata,
+                released: 0,
+                withdrawn: false,
+                cancelled: false,
+            };
+            self.transactions.insert(fund_id, &transaction);
+            self.index_by_creator(caller, fund_id);
+            let deposit = self.env().transferred_value();
+            if deposit > 0 {
+                self.deposits.insert(fund_id, &Deposit {
+                    amount: deposit,
+                    penalty_bps: self.cancellation_penalty_bps(),
+                });
+            }
+
+            self.env().emit_event(CampaignCreated {
+                fund_id,
+                creator: caller,
+                expected_value,
+                deadline,
+            });
+            Ok(fund_id)
+        }
+
+        /// Read transaction by its id
+        #[ink(message)]
+        pub fn get_funding(&self, fund_id: FundingId) -> Option<Transaction> {
+            let transaction = self.transactions.get(&fund_id);
+            transaction
+        }
+
+        /// Replace the metadata of `fund_id`.
+        ///
+        /// Can only be called by the creator while the campaign is open.
+        #[ink(message)]
+        pub fn update_metadata(&mut self, fund_id: FundingId, metadata: CampaignMetadata) -> Result<()> {
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+            Self::ensure_valid_metadata(&metadata)?;
+
+            transaction.metadata = metadata;
+            self.transactions.insert(fund_id, &transaction);
+            self.env().emit_event(MetadataUpdated { fund_id });
+            Ok(())
+        }
+
+        /// Bound the contributions `fund_id` accepts by `limits`.
+        ///
+        /// The limits only apply to contributions from now on. Can only be called by
+        /// the creator while the campaign is open.
+        #[ink(message)]
+        pub fn set_contribution_limits(&mut self, fund_id: FundingId, limits: ContributionLimits) -> Result<()> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+            if let (Some(min), Some(max)) = (limits.min_contribution, limits.max_per_backer) {
+                if min > max {
+                    return Err(Error::InvalidLimits)
+                }
+            }
+
+            self.contribution_limits.insert(fund_id, &limits);
+            self.env().emit_event(ContributionLimitsUpdated { fund_id, limits });
+            Ok(())
+        }
+
+        /// Read the limits contributions to `fund_id` have to keep to
+        #[ink(message)]
+        pub fn contribution_limits(&self, fund_id: FundingId) -> ContributionLimits {
+            self.contribution_limits.get(fund_id).unwrap_or_default()
+        }
+
+        /// Read the number of backers with a contribution in `fund_id`
+        #[ink(message)]
+        pub fn backer_count(&self, fund_id: FundingId) -> u32 {
+            self.backer_count.get(fund_id).unwrap_or(0)
+        }
+
+        /// Open `fund_id` to anyone, or restrict it as `access` says.
+        ///
+        /// Can only be called by the creator while the campaign is open. Accounts
+        /// that already backed the campaign keep their contribution either way.
+        #[ink(message)]
+        pub fn set_access(&mut self, fund_id: FundingId, access: CampaignAccess) -> Result<()> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+
+            self.access.insert(fund_id, &access);
+            self.env().emit_event(AccessChanged { fund_id, access });
+            Ok(())
+        }
+
+        /// Read who may back `fund_id`
+        #[ink(message)]
+        pub fn access(&self, fund_id: FundingId) -> CampaignAccess {
+            self.access.get(fund_id).unwrap_or_default()
+        }
+
+        /// Let `accounts` back the private campaign `fund_id`.
+        ///
+        /// Takes at most `MAX_ALLOWLIST_BATCH` accounts. Can only be called by the creator.
+        #[ink(message)]
+        pub fn add_to_allowlist(&mut self, fund_id: FundingId, accounts: Vec<AccountId>) -> Result<()> {
+            self.update_allowlist(fund_id, accounts, true)
+        }
+
+        /// Stop `accounts` from backing the private campaign `fund_id` any further.
+        ///
+        /// Takes at most `MAX_ALLOWLIST_BATCH` accounts. Can only be called by the creator.
+        #[ink(message)]
+        pub fn remove_from_allowlist(&mut self, fund_id: FundingId, accounts: Vec<AccountId>) -> Result<()> {
+            self.update_allowlist(fund_id, accounts, false)
+        }
+
+        /// Read whether `account` may back `fund_id` through `fund`
+        #[ink(message)]
+        pub fn is_allowed(&self, fund_id: FundingId, account: AccountId) -> bool {
+            match self.access(fund_id) {
+                CampaignAccess::Public => true,
+                _ => self.allowlist.get((fund_id, account)).unwrap_or(false),
+            }
+        }
+
+        /// Propose `beneficiary` as the new payout account of `fund_id`.
+        ///
+        /// The handover only happens once `beneficiary` calls `accept_beneficiary`.
+        /// Can only be called by the creator.
+        #[ink(message)]
+        pub fn propose_beneficiary(&mut self, fund_id: FundingId, beneficiary: AccountId) -> Result<()> {
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+
+            transaction.pending_beneficiary = Some(beneficiary);
+            self.transactions.insert(fund_id, &transaction);
+            self.env().emit_event(BeneficiaryProposed {
+                fund_id,
+                proposed: beneficiary,
+            });
+            Ok(())
+        }
+
+        /// Become the payout account of `fund_id` after its creator proposed the caller.
+        #[ink(message)]
+        pub fn accept_beneficiary(&mut self, fund_id: FundingId) -> Result<()> {
+            let caller = self.env().caller();
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            if transaction.pending_beneficiary != Some(caller) {
+                return Err(Error::NotPendingBeneficiary)
+            }
+
+            transaction.beneficiary = caller;
+            transaction.pending_beneficiary = None;
+            self.transactions.insert(fund_id, &transaction);
+            self.env().emit_event(BeneficiaryChanged {
+                fund_id,
+                beneficiary: caller,
+            });
+            Ok(())
+        }
+
+        /// Read the funding currently held in escrow for a transaction
+        #[ink(message)]
+        pub fn get_funding_status(&self, fund_id: FundingId) -> Option<Balance> {
+            self.current_funding.get(&fund_id)
+        }
+
+        /// Read the number of campaigns ever created
+        #[ink(message)]
+        pub fn campaign_count(&self) -> u32 {
+            self.transaction_list.next_id
+        }
+
+        /// List up to `limit` campaigns, starting at the `offset`-th one.
+        ///
+        /// At most `MAX_PAGE_SIZE` campaigns are returned per call.
+        #[ink(message)]
+        pub fn list_campaigns(&self, offset: u32, limit: u32) -> Vec<(FundingId, CampaignSummary)> {
+            let end = offset
+                .saturating_add(core::cmp::min(limit, MAX_PAGE_SIZE))
+                .min(self.transaction_list.next_id);
+            (offset..end)
+                .filter_map(|fund_id| self.campaign_summary(fund_id).map(|summary| (fund_id, summary)))
+                .collect()
+        }
+
+        /// List up to `limit` campaigns created by `creator`, starting at their `offset`-th one.
+        ///
+        /// At most `MAX_PAGE_SIZE` campaigns are returned per call.
+        #[ink(message)]
+        pub fn campaigns_by_creator(
+            &self,
+            creator: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<(FundingId, CampaignSummary)> {
+            let count = self.creator_count.get(creator).unwrap_or(0);
+            let end = offset
+                .saturating_add(core::cmp::min(limit, MAX_PAGE_SIZE))
+                .min(count);
+            (offset..end)
+                .filter_map(|index| self.by_creator.get((creator, index)))
+                .filter_map(|fund_id| self.campaign_summary(fund_id).map(|summary| (fund_id, summary)))
+                .collect()
+        }
+
+        /// Read the lifecycle state of a transaction
+        #[ink(message)]
+        pub fn get_campaign_state(&self, fund_id: FundingId) -> Option<CampaignState> {
+            self.transactions
+                .get(fund_id)
+                .map(|transaction| self.campaign_state(fund_id, &transaction))
+        }
+
+        #[ink(message, payable)]
+        pub fn fund(&mut self, fund_id: FundingId) -> Result<()> {
+            let value = self.env().transferred_value();
+            self.contribute(fund_id, None, value, false)
+        }
+
+        /// Fund `fund_id` and claim a slot of its reward tier `tier_id`.
+        ///
+        /// The pledge has to meet the tier's minimum, and every backer claims at
+        /// most one tier per campaign.
+        #[ink(message, payable)]
+        pub fn fund_tier(&mut self, fund_id: FundingId, tier_id: u32) -> Result<()> {
+            let value = self.env().transferred_value();
+            self.contribute(fund_id, Some(tier_id), value, false)
+        }
+
+        /// Prove that the caller is on the Merkle allowlist of `fund_id` and fund it.
+        ///
+        /// `proof` holds the sibling hashes from the caller's leaf up to the root.
+        /// Leaves are the BLAKE2 hash of the encoded account and every pair is hashed
+        /// in ascending order. Once proven, the caller can also use `fund`.
+        #[ink(message, payable)]
+        pub fn fund_with_proof(&mut self, fund_id: FundingId, proof: Vec<Hash>) -> Result<()> {
+            let caller = self.env().caller();
+            let root = match self.access(fund_id) {
+                CampaignAccess::MerkleRoot(root) => root,
+                _ => return Err(Error::InvalidProof),
+            };
+            if Self::merkle_root(Self::merkle_leaf(&caller), &proof) != root {
+                return Err(Error::InvalidProof)
+            }
+            self.allowlist.insert((fund_id, caller), &true);
+            let value = self.env().transferred_value();
+            self.contribute(fund_id, None, value, false)
+        }
+
+        /// Fund the token-denominated `fund_id` with `amount` of its PSP22 token.
+        ///
+        /// The caller has to `approve` the contract for `amount` on the token first.
+        /// Only the part the campaign accepts is pulled with `transfer_from`.
+        #[ink(message)]
+        pub fn fund_with_token(&mut self, fund_id: FundingId, amount: Balance) -> Result<()> {
+            if self.env().transferred_value() > 0 {
+                return Err(Error::WrongCurrency)
+            }
+            self.contribute(fund_id, None, amount, true)
+        }
+
+        /// Offer a new reward tier on `fund_id` and return its id.
+        ///
+        /// Can only be called by the creator while the campaign is open.
+        #[ink(message)]
+        pub fn add_reward_tier(
+            &mut self,
+            fund_id: FundingId,
+            min_pledge: Balance,
+            max_slots: u32,
+            metadata_hash: Hash,
+        ) -> Result<u32> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+
+            let mut tiers = self.reward_tiers.get(fund_id).unwrap_or_default();
+            if tiers.len() >= MAX_REWARD_TIERS {
+                return Err(Error::TooManyTiers)
+            }
+            let tier_id = tiers.len() as u32;
+            tiers.push(RewardTier {
+                min_pledge,
+                max_slots,
+                claimed: 0,
+                metadata_hash,
+            });
+            self.reward_tiers.insert(fund_id, &tiers);
+            self.env().emit_event(RewardTierAdded {
+                fund_id,
+                tier_id,
+                min_pledge,
+                max_slots,
+            });
+            Ok(tier_id)
+        }
+
+        /// Read the reward tiers of a transaction
+        #[ink(message)]
+        pub fn get_reward_tiers(&self, fund_id: FundingId) -> Vec<RewardTier> {
+            self.reward_tiers.get(fund_id).unwrap_or_default()
+        }
+
+        /// Read the number of free slots of a reward tier
+        #[ink(message)]
+        pub fn tier_remaining_slots(&self, fund_id: FundingId, tier_id: u32) -> Option<u32> {
+            self.reward_tiers
+                .get(fund_id)?
+                .get(tier_id as usize)
+                .map(|tier| tier.max_slots.saturating_sub(tier.claimed))
+        }
+
+        /// Read the reward tier `backer` claimed on a transaction
+        #[ink(message)]
+        pub fn claimed_tier(&self, fund_id: FundingId, backer: AccountId) -> Option<u32> {
+            self.claimed_tiers.get((fund_id, backer))
+        }
+
+        /// Let `fund_id` keep taking funding past its goal, up to `hard_cap`, toward
+        /// the `stretch_goals` of (amount, metadata hash).
+        ///
+        /// The amounts have to be ascending, above the goal and within the hard cap.
+        /// An empty list and a hard cap of `0` stop the campaign at its goal again,
+        /// which a campaign that raised past its goal can only do if it accepts
+        /// overfunding. Contributions past the hard cap are handled as the overflow policy says.
+        /// Can only be called by the creator up to the deadline, also once the goal
+        /// is reached, as long as the funding was not withdrawn.
+        #[ink(message)]
+        pub fn set_stretch_goals(
+            &mut self,
+            fund_id: FundingId,
+            stretch_goals: Vec<(Balance, Hash)>,
+            hard_cap: Balance,
+        ) -> Result<()> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            match self.campaign_state(fund_id, &transaction) {
+                CampaignState::Open => (),
+                CampaignState::Succeeded if self.env().block_number() <= transaction.deadline => (),
+                CampaignState::Succeeded | CampaignState::Failed => return Err(Error::DeadlinePassed),
+                CampaignState::Withdrawn => return Err(Error::AlreadyWithdrawn),
+                CampaignState::Cancelled => return Err(Error::CampaignCancelled),
+            }
+
+            if stretch_goals.is_empty() && hard_cap == 0 {
+                if transaction.overflow_policy != OverflowPolicy::Accept
+                    && self.raised(fund_id, &transaction) > transaction.expected_value
+                {
+                    return Err(Error::InvalidStretchGoals)
+                }
+                self.stretch_goals.remove(fund_id);
+                self.hard_caps.remove(fund_id);
+                self.env().emit_event(StretchGoalsSet {
+                    fund_id,
+                    stretch_goals: 0,
+                    hard_cap: None,
+                });
+                return Ok(())
+            }
+            if stretch_goals.len() > MAX_STRETCH_GOALS
+                || hard_cap < transaction.expected_value
+                || hard_cap < self.raised(fund_id, &transaction)
+            {
+                return Err(Error::InvalidStretchGoals)
+            }
+            let mut previous = transaction.expected_value;
+            for (amount, _) in &stretch_goals {
+                if *amount <= previous || *amount > hard_cap {
+                    return Err(Error::InvalidStretchGoals)
+                }
+                previous = *amount;
+            }
+
+            let stretch_goals: Vec<StretchGoal> = stretch_goals
+                .into_iter()
+                .map(|(amount, metadata_hash)| StretchGoal { amount, metadata_hash })
+                .collect();
+            self.stretch_goals.insert(fund_id, &stretch_goals);
+            self.hard_caps.insert(fund_id, &hard_cap);
+            self.env().emit_event(StretchGoalsSet {
+                fund_id,
+                stretch_goals: stretch_goals.len() as u32,
+                hard_cap: Some(hard_cap),
+            });
+            Ok(())
+        }
+
+        /// Read the stretch goals of a transaction
+        #[ink(message)]
+        pub fn get_stretch_goals(&self, fund_id: FundingId) -> Vec<StretchGoal> {
+            self.stretch_goals.get(fund_id).unwrap_or_default()
+        }
+
+        /// Read the hard cap of a transaction, if it has stretch goals
+        #[ink(message)]
+        pub fn hard_cap(&self, fund_id: FundingId) -> Option<Balance> {
+            self.hard_caps.get(fund_id)
+        }
+
+        /// Read the number of stretch goals a transaction has unlocked
+        #[ink(message)]
+        pub fn stretch_level(&self, fund_id: FundingId) -> u32 {
+            let transaction = match self.transactions.get(fund_id) {
+                Some(transaction) => transaction,
+                None => return 0,
+            };
+            let raised = self.raised(fund_id, &transaction);
+            self.get_stretch_goals(fund_id)
+                .iter()
+                .filter(|goal| goal.amount <= raised)
+                .count() as u32
+        }
+
+        /// Set the transferred value aside to match contributions to campaigns.
+        ///
+        /// Every contribution to an attached campaign is matched at `ratio_bps`, up to
+        /// `cap_per_campaign` per campaign and for as long as the pool lasts.
+        #[ink(message, payable)]
+        pub fn create_sponsor_pool(&mut self, ratio_bps: u16, cap_per_campaign: Balance) -> Result<PoolId> {
+            self.ensure_not_paused()?;
+            if ratio_bps == 0 || cap_per_campaign == 0 {
+                return Err(Error::InvalidMatchRatio)
+            }
+            let sponsor = self.env().caller();
+            let amount = self.env().transferred_value();
+
+            let pool_id = self.next_pool_id.get(()).unwrap_or(0);
+            self.next_pool_id.insert((), &pool_id.checked_add(1).ok_or(Error::IdsExhausted)?);
+            self.sponsor_pools.insert(
+                pool_id,
+                &SponsorPool {
+                    sponsor,
+                    available: amount,
+                    ratio_bps,
+                    cap_per_campaign,
+                    campaigns: Vec::new(),
+                },
+            );
+            self.env().emit_event(SponsorPoolCreated {
+                pool_id,
+                sponsor,
+                amount,
+            });
+            Ok(pool_id)
+        }
+
+        /// Let `pool_id` match the contributions to `fund_id` from now on.
+        ///
+        /// A campaign is matched by at most one pool, and only campaigns in the native
+        /// balance can be matched. Can only be called by the sponsor while the
+        /// campaign is open.
+        #[ink(message)]
+        pub fn attach_pool(&mut self, pool_id: PoolId, fund_id: FundingId) -> Result<()> {
+            let mut pool = self.ensure_sponsor(pool_id)?;
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+            if transaction.currency.is_some() {
+                return Err(Error::WrongCurrency)
+            }
+            if self.campaign_pools.get(fund_id).is_some() {
+                return Err(Error::PoolAlreadyAttached)
+            }
+            if pool.campaigns.len() >= MAX_POOL_CAMPAIGNS {
+                return Err(Error::TooManyPoolCampaigns)
+            }
+
+            pool.campaigns.push(fund_id);
+            self.sponsor_pools.insert(pool_id, &pool);
+            self.campaign_pools.insert(fund_id, &pool_id);
+            self.env().emit_event(PoolAttached { pool_id, fund_id });
+            Ok(())
+        }
+
+        /// Pay the sponsor of `pool_id` back what the pool did not match, together
+        /// with the matches of campaigns that failed or were cancelled.
+        ///
+        /// Only possible once none of the attached campaigns takes funding any more.
+        #[ink(message)]
+        pub fn reclaim_pool(&mut self, pool_id: PoolId) -> Result<()> {
+            let mut pool = self.ensure_sponsor(pool_id)?;
+            let mut campaigns = Vec::new();
+            for &fund_id in &pool.campaigns {
+                let transaction = self.ensure_transaction_exists(fund_id)?;
+                if self.accepts_funding(fund_id, &transaction) {
+                    return Err(Error::PoolCampaignsOpen)
+                }
+                campaigns.push((fund_id, self.campaign_state(fund_id, &transaction)));
+            }
+
+            let mut amount = pool.available;
+            for (fund_id, state) in campaigns {
+                if state != CampaignState::Failed && state != CampaignState::Cancelled {
+                    continue
+                }
+                let matched = self.pool_matches.get(fund_id).unwrap_or(0);
+                let unspent = self.take_unspent_match(fund_id, matched)?;
+                self.pool_matches.insert(fund_id, &(matched - unspent));
+                amount = amount.checked_add(unspent).ok_or(Error::Overflow)?;
+            }
+            if amount == 0 {
+                return Err(Error::NothingToReclaim)
+            }
+
+            pool.available = 0;
+            self.sponsor_pools.insert(pool_id, &pool);
+            self.transfer_out(None, pool.sponsor, amount)?;
+            self.env().emit_event(PoolReclaimed { pool_id, amount });
+            Ok(())
+        }
+
+        /// Read sponsor pool by its id
+        #[ink(message)]
+        pub fn get_sponsor_pool(&self, pool_id: PoolId) -> Option<SponsorPool> {
+            self.sponsor_pools.get(pool_id)
+        }
+
+        /// Read the part of a transaction's escrow its sponsor pool paid in
+        #[ink(message)]
+        pub fn matched_amount(&self, fund_id: FundingId) -> Balance {
+            self.pool_matches.get(fund_id).unwrap_or(0)
+        }
+
+        /// Open a quadratic funding round with the transferred value as matching pool.
+        ///
+        /// Contributions count toward the round from block `start` up to and
+        /// including block `end`.
+        #[ink(message, payable)]
+        pub fn create_round(&mut self, start: BlockNumber, end: BlockNumber) -> Result<RoundId> {
+            self.ensure_not_paused()?;
+            if start > end || end < self.env().block_number() {
+                return Err(Error::InvalidRound)
+            }
+            let operator = self.env().caller();
+            let matching_pool = self.env().transferred_value();
+
+            let round_id = self.next_round_id.get(()).unwrap_or(0);
+            self.next_round_id.insert((), &round_id.checked_add(1).ok_or(Error::IdsExhausted)?);
+            self.rounds.insert(
+                round_id,
+                &Round {
+                    operator,
+                    matching_pool,
+                    start,
+                    end,
+                    campaigns: Vec::new(),
+                    finalized: false,
+                },
+            );
+            self.env().emit_event(RoundCreated {
+                round_id,
+                operator,
+                matching_pool,
+                start,
+                end,
+            });
+            Ok(round_id)
+        }
+
+        /// Enter `fund_id` into the round `round_id`.
+        ///
+        /// Only contributions made after joining count toward the round. Can only be
+        /// called by the creator of an open campaign in the native balance before the
+        /// round ends.
+        #[ink(message)]
+        pub fn join_round(&mut self, round_id: RoundId, fund_id: FundingId) -> Result<()> {
+            let mut round = self.rounds.get(round_id).ok_or(Error::UnknownRound)?;
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+            if transaction.currency.is_some() {
+                return Err(Error::WrongCurrency)
+            }
+            if self.env().block_number() > round.end {
+                return Err(Error::RoundEnded)
+            }
+            if self.campaign_rounds.get(fund_id).is_some() {
+                return Err(Error::AlreadyInRound)
+            }
+            if round.campaigns.len() >= MAX_ROUND_CAMPAIGNS {
+                return Err(Error::TooManyRoundCampaigns)
+            }
+
+            round.campaigns.push(fund_id);
+            self.rounds.insert(round_id, &round);
+            self.campaign_rounds.insert(fund_id, &round_id);
+            self.env().emit_event(RoundJoined { round_id, fund_id });
+            Ok(())
+        }
+
+        /// Split the matching pool of `round_id` across its campaigns.
+        ///
+        /// Campaigns that failed or no longer hold their funding get nothing, and no
+        /// campaign is matched past what it can still raise. What these caps and
+        /// rounding leave over is paid back to the operator, and so is the whole pool
+        /// if the scores do not fit a `Balance`. Anyone can finalize a round once it
+        /// has ended.
+        #[ink(message)]
+        pub fn finalize_round(&mut self, round_id: RoundId) -> Result<()> {
+            let mut round = self.rounds.get(round_id).ok_or(Error::UnknownRound)?;
+            if round.finalized {
+                return Err(Error::RoundFinalized)
+            }
+            if self.env().block_number() <= round.end {
+                return Err(Error::RoundNotEnded)
+            }
+
+            let (scores, total_score) = self.round_scores(&round).unwrap_or_default();
+            let mut distributed: Balance = 0;
+            for (fund_id, score, room) in scores {
+                // `score <= total_score`, so the share never exceeds the pool.
+                let share = match Self::mul_div(round.matching_pool, score, total_score) {
+                    Some(share) => share,
+                    None => continue,
+                };
+                let amount = match room {
+                    Some(room) => core::cmp::min(share, room),
+                    None => share,
+                };
+                if amount == 0 {
+                    continue
+                }
+                self.lock_escrow(fund_id, None, amount)?;
+                self.round_matches.insert(fund_id, &amount);
+                distributed += amount;
+                self.env().emit_event(RoundMatched {
+                    round_id,
+                    fund_id,
+                    amount,
+                });
+            }
+
+            round.finalized = true;
+            self.rounds.insert(round_id, &round);
+            let leftover = round.matching_pool - distributed;
+            if leftover > 0 {
+                self.transfer_out(None, round.operator, leftover)?;
+            }
+            self.env().emit_event(RoundFinalized { round_id, distributed });
+            Ok(())
+        }
+
+        /// Pay the operator of `round_id` back the matches of campaigns that failed
+        /// or were cancelled.
+        ///
+        /// Only possible once the round was finalized and none of its campaigns takes
+        /// funding any more.
+        #[ink(message)]
+        pub fn reclaim_round(&mut self, round_id: RoundId) -> Result<()> {
+            let round = self.rounds.get(round_id).ok_or(Error::UnknownRound)?;
+            if round.operator != self.env().caller() {
+                return Err(Error::NotRoundOperator)
+            }
+            if !round.finalized {
+                return Err(Error::RoundNotFinalized)
+            }
+            let mut failed = Vec::new();
+            for &fund_id in &round.campaigns {
+                let transaction = self.ensure_transaction_exists(fund_id)?;
+                if self.accepts_funding(fund_id, &transaction) {
+                    return Err(Error::PoolCampaignsOpen)
+                }
+                match self.campaign_state(fund_id, &transaction) {
+                    CampaignState::Failed | CampaignState::Cancelled => failed.push(fund_id),
+                    _ => (),
+                }
+            }
+
+            let mut amount: Balance = 0;
+            for fund_id in failed {
+                let matched = self.round_matches.get(fund_id).unwrap_or(0);
+                let unspent = self.take_unspent_match(fund_id, matched)?;
+                self.round_matches.insert(fund_id, &(matched - unspent));
+                amount = amount.checked_add(unspent).ok_or(Error::Overflow)?;
+            }
+            if amount == 0 {
+                return Err(Error::NothingToReclaim)
+            }
+
+            self.transfer_out(None, round.operator, amount)?;
+            self.env().emit_event(RoundReclaimed { round_id, amount });
+            Ok(())
+        }
+
+        /// Read quadratic funding round by its id
+        #[ink(message)]
+        pub fn get_round(&self, round_id: RoundId) -> Option<Round> {
+            self.rounds.get(round_id)
+        }
+
+        /// Read what `backer` contributed to `fund_id` while its round was running
+        #[ink(message)]
+        pub fn round_contribution(&self, fund_id: FundingId, backer: AccountId) -> Balance {
+            self.round_contributions.get((fund_id, backer)).unwrap_or(0)
+        }
+
+        /// Read the part of a transaction's escrow its round paid in
+        #[ink(message)]
+        pub fn round_match(&self, fund_id: FundingId) -> Balance {
+            self.round_matches.get(fund_id).unwrap_or(0)
+        }
+
+        /// Read the id of the receipt collection, which is the contract's account
+        #[ink(message, selector = 0xffa27a5f)]
+        pub fn collection_id(&self) -> Id {
+            Id::Bytes(scale::Encode::encode(&self.env().account_id()))
+        }
+
+        /// Read the number of receipts `owner` holds
+        #[ink(message, selector = 0xcde7e55f)]
+        pub fn balance_of(&self, owner: AccountId) -> u32 {
+            self.receipt_balances.get(owner).unwrap_or(0)
+        }
+
+        /// Read the holder of receipt `id`
+        #[ink(message, selector = 0x1168624d)]
+        pub fn owner_of(&self, id: Id) -> Option<AccountId> {
+            self.receipt_owners.get(Self::receipt_id(&id)?)
+        }
+
+        /// Read whether `operator` may transfer receipt `id` of `owner`, or all of
+        /// them if no `id` is given
+        #[ink(message, selector = 0x4790f55a)]
+        pub fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
+            if self.receipt_approvals.get((owner, operator, None::<ReceiptId>)).unwrap_or(false) {
+                return true
+            }
+            match id.as_ref().and_then(Self::receipt_id) {
+                Some(id) => self.receipt_approvals.get((owner, operator, Some(id))).unwrap_or(false),
+                None => false,
+            }
+        }
+
+        /// Let `operator` transfer receipt `id` of the caller, or all of them if no
+        /// `id` is given, or stop them if not `approved`.
+        #[ink(message, selector = 0x1932a8b0)]
+        pub fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool,
+        ) -> core::result::Result<(), PSP34Error> {
+            let owner = self.env().caller();
+            if owner == operator {
+                return Err(PSP34Error::SelfApprove)
+            }
+            let receipt_id = match &id {
+                Some(id) => {
+                    let receipt_id = Self::receipt_id(id).ok_or(PSP34Error::TokenNotExists)?;
+                    if self.receipt_owners.get(receipt_id) != Some(owner) {
+                        return Err(PSP34Error::NotApproved)
+                    }
+                    Some(receipt_id)
+                }
+                None => None,
+            };
+
+            if approved {
+                self.receipt_approvals.insert((owner, operator, receipt_id), &true);
+            } else {
+                self.receipt_approvals.remove((owner, operator, receipt_id));
+            }
+            self.env().emit_event(Approval {
+                owner,
+                operator,
+                id,
+                approved,
+            });
+            Ok(())
+        }
+
+        /// Hand receipt `id` over to `to`.
+        ///
+        /// Can be called by the holder or an operator they approved. The receipt only
+        /// proves support, so refunds and retractions still go to the backer.
+        #[ink(message, selector = 0x3128d61b)]
+        pub fn transfer(&mut self, to: AccountId, id: Id, _data: Vec<u8>) -> core::result::Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            let receipt_id = Self::receipt_id(&id).ok_or(PSP34Error::TokenNotExists)?;
+            let owner = self.receipt_owners.get(receipt_id).ok_or(PSP34Error::TokenNotExists)?;
+            if caller != owner && !self.allowance(owner, caller, Some(id.clone())) {
+                return Err(PSP34Error::NotApproved)
+            }
+
+            self.receipt_approvals.remove((owner, caller, Some(receipt_id)));
+            self.receipt_owners.insert(receipt_id, &to);
+            self.receipt_balances.insert(owner, &self.balance_of(owner).saturating_sub(1));
+            self.receipt_balances.insert(to, &(self.balance_of(to) + 1));
+            self.env().emit_event(Transfer {
+                from: Some(owner),
+                to: Some(to),
+                id,
+            });
+            Ok(())
+        }
+
+        /// Read the number of receipts in existence
+        #[ink(message, selector = 0x628413fe)]
+        pub fn total_supply(&self) -> Balance {
+            self.receipt_supply.get(()).unwrap_or(0)
+        }
+
+        /// Read what receipt `id` records
+        #[ink(message)]
+        pub fn get_receipt(&self, id: Id) -> Option<Receipt> {
+            self.receipts.get(Self::receipt_id(&id)?)
+        }
+
+        /// Read the id of the receipt for `backer`'s contribution to a transaction
+        #[ink(message)]
+        pub fn receipt_of(&self, fund_id: FundingId, backer: AccountId) -> Option<Id> {
+            self.backer_receipts.get((fund_id, backer)).map(Id::U64)
+        }
+
+        /// Pay the funding of `fund_id` out to its beneficiary.
+        ///
+        /// Can be called by the creator or the beneficiary. Only the campaign's own
+        /// escrow is paid out, all of it and only once. Contributions past the goal
+        /// or hard cap are turned away as they come in, so the escrow never holds
+        /// more than the campaign may raise. The platform fee the campaign was
+        /// created with goes to the `treasury`.
+        ///
+        /// Keep-it-all campaigns can be withdrawn from while they are still open.
+        /// Such partial withdrawals pay out the escrow so far and leave the campaign
+        /// open; only the withdrawal after the campaign stopped taking funding is final.
+        ///
+        /// Campaigns with milestones pay out through `finalize_milestone` instead.
+        #[ink(message)]
+        pub fn withdraw(&mut self, fund_id: FundingId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_manager(&transaction, self.env().caller())?;
+            self.ensure_campaign_withdrawable(fund_id, &transaction)?;
+            if !self.milestones.get(fund_id).unwrap_or_default().is_empty() {
+                return Err(Error::PaysOutInMilestones)
+            }
+
+            let funding = self.current_funding.get(fund_id).unwrap_or(0);
+            self.release_escrow(fund_id, transaction.currency, funding)?;
+
+            let fee = Self::platform_fee(funding, transaction.fee_bps)?;
+            self.pay_out(transaction.currency, transaction.beneficiary, funding - fee, fee)?;
+
+            if transaction.funding_model == FundingModel::KeepItAll
+                && self.accepts_funding(fund_id, &transaction)
+            {
+                transaction.released = transaction.released.saturating_add(funding);
+            } else {
+                transaction.withdrawn = true;
+            }
+            self.transactions.insert(fund_id, &transaction);
+            self.env().emit_event(Withdrawn {
+                fund_id,
+                to: transaction.beneficiary,
+                amount: funding - fee,
+                fee,
+            });
+            Ok(())
+        }
+
+        /// Call off `fund_id` so that every backer can claim their contribution back
+        /// with `refund`.
+        ///
+        /// Can only be called by the creator while the campaign is open and nothing
+        /// was paid out, and not for campaigns carried over by `migrate`. The deposit
+        /// is returned less the cancellation penalty, which goes to the `treasury`.
+        #[ink(message)]
+        pub fn cancel_funding(&mut self, fund_id: FundingId) -> Result<()> {
+            let caller = self.env().caller();
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, caller)?;
+            self.ensure_not_legacy(fund_id)?;
+            match self.campaign_state(fund_id, &transaction) {
+                CampaignState::Open => (),
+                CampaignState::Succeeded => return Err(Error::GoalAlreadyReached),
+                CampaignState::Failed => return Err(Error::DeadlinePassed),
+                CampaignState::Withdrawn => return Err(Error::AlreadyWithdrawn),
+                CampaignState::Cancelled => return Err(Error::CampaignCancelled),
+            }
+            if transaction.released > 0 {
+                return Err(Error::AlreadyWithdrawn)
+            }
+
+            transaction.cancelled = true;
+            self.transactions.insert(fund_id, &transaction);
+
+            if let Some(Deposit { amount: deposit, penalty_bps }) = self.deposits.get(fund_id) {
+                let penalty = Self::platform_fee(deposit, penalty_bps)?;
+                self.deposits.remove(fund_id);
+                self.pay_out(None, caller, deposit - penalty, penalty)?;
+                self.env().emit_event(DepositReturned {
+                    fund_id,
+                    to: caller,
+                    amount: deposit - penalty,
+                    penalty,
+                });
+            }
+            self.env().emit_event(CampaignCancelled { fund_id, by: caller });
+            Ok(())
+        }
+
+        /// Pay the creator's deposit for `fund_id` back in full.
+        ///
+        /// Can only be called by the creator once the campaign has closed without
+        /// being cancelled.
+        #[ink(message)]
+        pub fn reclaim_deposit(&mut self, fund_id: FundingId) -> Result<()> {
+            let caller = self.env().caller();
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, caller)?;
+            match self.campaign_state(fund_id, &transaction) {
+                CampaignState::Open => return Err(Error::CampaignOpen),
+                CampaignState::Cancelled => return Err(Error::CampaignCancelled),
+                _ => (),
+            }
+            let deposit = self.deposits.get(fund_id).ok_or(Error::NoDeposit)?.amount;
+
+            self.deposits.remove(fund_id);
+            self.transfer_out(None, caller, deposit)?;
+            self.env().emit_event(DepositReturned {
+                fund_id,
+                to: caller,
+                amount: deposit,
+                penalty: 0,
+            });
+            Ok(())
+        }
+
+        /// Read the deposit the creator of `fund_id` still has put down
+        #[ink(message)]
+        pub fn get_deposit(&self, fund_id: FundingId) -> Option<Deposit> {
+            self.deposits.get(fund_id)
+        }
+
+        /// Pay the caller back what they put into `fund_id`.
+        ///
+        /// Only possible once the campaign has failed or was cancelled. If milestones
+        /// were already paid out, backers share what is left in escrow pro rata.
+        /// Refunds keep working while the contract is paused.
+        #[ink(message)]
+        pub fn refund(&mut self, fund_id: FundingId) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_migrated()?;
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_refundable(fund_id, &transaction)?;
+
+            let contributed = self
+                .contributions
+                .get((fund_id, caller))
+                .ok_or(Error::NothingToRefund)?;
+            let escrow = self.current_funding.get(fund_id).unwrap_or(0);
+            let total = self.total_contributions.get(fund_id).unwrap_or(0);
+            let amount = if escrow >= total {
+                contributed
+            } else {
+                // `contributed <= total`, so the share never exceeds the escrow.
+                contributed
+                    .checked_mul(escrow)
+                    .ok_or(Error::Overflow)?
+                    / total
+            };
+            self.release_escrow(fund_id, transaction.currency, amount)?;
+            self.contributions.remove((fund_id, caller));
+            self.total_contributions.insert(fund_id, &total.saturating_sub(contributed));
+            self.backer_count.insert(fund_id, &self.backer_count(fund_id).saturating_sub(1));
+            self.release_tier(fund_id, caller);
+            self.sync_receipt(fund_id, caller);
+
+            self.transfer_out(transaction.currency, caller, amount)?;
+            self.env().emit_event(Refunded {
+                fund_id,
+                backer: caller,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Take back `amount` of the caller's contribution to `fund_id`.
+        ///
+        /// Only possible while the campaign is open and nothing was paid out, and as
+        /// far as its retraction policy allows. A reward tier whose minimum the rest
+        /// of the contribution no longer meets is given up, and the sponsor match the
+        /// amount earned goes back to its pool.
+        #[ink(message)]
+        pub fn unfund(&mut self, fund_id: FundingId, amount: Balance) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused()?;
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            match self.campaign_state(fund_id, &transaction) {
+                CampaignState::Open => (),
+                CampaignState::Succeeded => return Err(Error::GoalAlreadyReached),
+                CampaignState::Failed => return Err(Error::DeadlinePassed),
+                CampaignState::Withdrawn => return Err(Error::AlreadyWithdrawn),
+                CampaignState::Cancelled => return Err(Error::CampaignCancelled),
+            }
+            if transaction.released > 0 {
+                return Err(Error::AlreadyWithdrawn)
+            }
+            match self.retraction_policy(fund_id) {
+                RetractionPolicy::Allowed => (),
+                RetractionPolicy::Disabled => return Err(Error::RetractionDisabled),
+                RetractionPolicy::CutOff(blocks) => {
+                    if self.env().block_number() > transaction.deadline.saturating_sub(blocks) {
+                        return Err(Error::RetractionClosed)
+                    }
+                }
+            }
+            if amount == 0 {
+                return Err(Error::ZeroContribution)
+            }
+            let contributed = self
+                .contributions
+                .get((fund_id, caller))
+                .ok_or(Error::NothingToRefund)?;
+            let rest = contributed
+                .checked_sub(amount)
+                .ok_or(Error::AmountExceedsContribution)?;
+
+            self.reverse_match(fund_id, caller, amount, contributed)?;
+            self.untrack_round_contribution(fund_id, caller, amount)?;
+            self.release_escrow(fund_id, transaction.currency, amount)?;
+            let total = self.total_contributions.get(fund_id).unwrap_or(0);
+            self.total_contributions.insert(fund_id, &total.saturating_sub(amount));
+            if rest == 0 {
+                self.contributions.remove((fund_id, caller));
+                self.backer_count.insert(fund_id, &self.backer_count(fund_id).saturating_sub(1));
+                self.release_tier(fund_id, caller);
+            } else {
+                self.contributions.insert((fund_id, caller), &rest);
+                let tier_met = self
+                    .claimed_tiers
+                    .get((fund_id, caller))
+                    .and_then(|tier_id| {
+                        self.get_reward_tiers(fund_id)
+                            .get(tier_id as usize)
+                            .map(|tier| rest >= tier.min_pledge)
+                    })
+                    .unwrap_or(true);
+                if !tier_met {
+                    self.release_tier(fund_id, caller);
+                }
+            }
+            self.sync_receipt(fund_id, caller);
+
+            self.transfer_out(transaction.currency, caller, amount)?;
+            self.env().emit_event(Unfunded {
+                fund_id,
+                backer: caller,
+                amount,
+                total: self.raised(fund_id, &transaction),
+            });
+            Ok(())
+        }
+
+        /// Decide whether and until when backers may retract their contributions to `fund_id`.
+        ///
+        /// Can only be called by the creator while the campaign is open.
+        #[ink(message)]
+        pub fn set_retraction_policy(&mut self, fund_id: FundingId, policy: RetractionPolicy) -> Result<()> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+
+            self.retraction_policies.insert(fund_id, &policy);
+            self.env().emit_event(RetractionPolicyChanged { fund_id, policy });
+            Ok(())
+        }
+
+        /// Read whether and until when backers may retract their contributions to a transaction
+        #[ink(message)]
+        pub fn retraction_policy(&self, fund_id: FundingId) -> RetractionPolicy {
+            self.retraction_policies.get(fund_id).unwrap_or_default()
+        }
+
+        /// Split the funding of `fund_id` into `milestones` of (amount, deadline),
+        /// each released after a vote of `voting_period` blocks.
+        ///
+        /// The amounts must add up to the goal and the deadlines must come after
+        /// the campaign's deadline, in ascending order. The voting period has to be
+        /// at least `MIN_VOTING_PERIOD`. An empty list pays the funding out at once
+        /// again. Can only be called by the creator of an all-or-nothing campaign
+        /// while it is open and has not received any funding.
+        #[ink(message)]
+        pub fn set_milestones(
+            &mut self,
+            fund_id: FundingId,
+            milestones: Vec<(Balance, BlockNumber)>,
+            voting_period: BlockNumber,
+        ) -> Result<()> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            self.ensure_campaign_open(fund_id, &transaction)?;
+            if transaction.funding_model != FundingModel::AllOrNothing {
+                return Err(Error::UnsupportedFundingModel)
+            }
+            if self.total_contributions.get(fund_id).unwrap_or(0) > 0 {
+                return Err(Error::MilestonesLocked)
+            }
+            if milestones.len() > MAX_MILESTONES
+                || (!milestones.is_empty() && voting_period < MIN_VOTING_PERIOD)
+            {
+                return Err(Error::InvalidMilestones)
+            }
+
+            let mut total: Balance = 0;
+            let mut previous_deadline = transaction.deadline;
+            for (amount, deadline) in milestones.iter() {
+                if *deadline <= previous_deadline {
+                    return Err(Error::InvalidMilestones)
+                }
+                previous_deadline = *deadline;
+                total = total.checked_add(*amount).ok_or(Error::Overflow)?;
+            }
+            if !milestones.is_empty() && total != transaction.expected_value {
+                return Err(Error::InvalidMilestones)
+            }
+
+            let milestones: Vec<Milestone> = milestones
+                .into_iter()
+                .map(|(amount, deadline)| Milestone {
+                    amount,
+                    deadline,
+                    status: MilestoneStatus::Pending,
+                    voting_ends: 0,
+                    approvals: 0,
+                    rejections: 0,
+                })
+                .collect();
+            self.milestones.insert(fund_id, &milestones);
+            if milestones.is_empty() {
+                self.voting_periods.remove(fund_id);
+            } else {
+                self.voting_periods.insert(fund_id, &voting_period);
+            }
+            self.env().emit_event(MilestonesSet {
+                fund_id,
+                milestones: milestones.len() as u32,
+                voting_period: self.voting_period(fund_id),
+            });
+            Ok(())
+        }
+
+        /// Read the milestones of a transaction
+        #[ink(message)]
+        pub fn get_milestones(&self, fund_id: FundingId) -> Vec<Milestone> {
+            self.milestones.get(fund_id).unwrap_or_default()
+        }
+
+        /// Read the number of blocks backers vote on each milestone release of a transaction
+        #[ink(message)]
+        pub fn voting_period(&self, fund_id: FundingId) -> BlockNumber {
+            self.voting_periods.get(fund_id).unwrap_or(MIN_VOTING_PERIOD)
+        }
+
+        /// Ask the backers of `fund_id` to approve the release of the next milestone.
+        ///
+        /// Starts a vote of the campaign's voting period. Can be called by the creator or
+        /// the beneficiary once the campaign succeeded, up to the milestone's deadline.
+        #[ink(message)]
+        pub fn request_milestone_release(&mut self, fund_id: FundingId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_manager(&transaction, self.env().caller())?;
+            self.ensure_campaign_succeeded(fund_id, &transaction)?;
+
+            let mut milestones = self.milestones.get(fund_id).unwrap_or_default();
+            let index = Self::current_milestone(&milestones).ok_or(Error::NoPendingMilestone)?;
+            let now = self.env().block_number();
+            let milestone = &mut milestones[index];
+            if milestone.status != MilestoneStatus::Pending {
+                return Err(Error::ReleaseAlreadyRequested)
+            }
+            if now > milestone.deadline {
+                return Err(Error::MilestoneDeadlinePassed)
+            }
+
+            milestone.status = MilestoneStatus::Voting;
+            milestone.voting_ends = now.saturating_add(self.voting_period(fund_id));
+            let voting_ends = milestone.voting_ends;
+            self.milestones.insert(fund_id, &milestones);
+            self.env().emit_event(MilestoneReleaseRequested {
+                fund_id,
+                milestone: index as u32,
+                voting_ends,
+            });
+            Ok(())
+        }
+
+        /// Vote on the release of the current milestone of `fund_id`.
+        ///
+        /// The vote weighs as much as the caller's contribution. Every backer votes
+        /// at most once per milestone.
+        #[ink(message)]
+        pub fn vote_milestone(&mut self, fund_id: FundingId, approve: bool) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_transaction_exists(fund_id)?;
+            let weight = self
+                .contributions
+                .get((fund_id, caller))
+                .ok_or(Error::NotBacker)?;
+
+            let mut milestones = self.milestones.get(fund_id).unwrap_or_default();
+            let index = Self::current_milestone(&milestones).ok_or(Error::NoPendingMilestone)?;
+            let milestone = &mut milestones[index];
+            if milestone.status != MilestoneStatus::Voting
+                || self.env().block_number() > milestone.voting_ends
+            {
+                return Err(Error::NoVoteRunning)
+            }
+            let vote_key = (fund_id, index as u32, caller);
+            if self.milestone_votes.get(vote_key).unwrap_or(false) {
+                return Err(Error::AlreadyVoted)
+            }
+
+            if approve {
+                milestone.approvals = milestone.approvals.saturating_add(weight);
+            } else {
+                milestone.rejections = milestone.rejections.saturating_add(weight);
+            }
+            self.milestone_votes.insert(vote_key, &true);
+            self.milestones.insert(fund_id, &milestones);
+            self.env().emit_event(MilestoneVoted {
+                fund_id,
+                backer: caller,
+                milestone: index as u32,
+                approve,
+                weight,
+            });
+            Ok(())
+        }
+
+        /// Settle the current milestone of `fund_id` once its vote is over.
+        ///
+        /// The milestone is approved if backers holding at least `MILESTONE_QUORUM_BPS`
+        /// of the contributions approve it and rejections do not outweigh approvals,
+        /// and its tranche is paid out to the beneficiary. A vote nobody takes part in
+        /// rejects the milestone. A rejected milestone, or one whose
+        /// deadline passed without a release request, fails the campaign so that
+        /// backers can reclaim the rest of the escrow. Can be called by anyone.
+        #[ink(message)]
+        pub fn finalize_milestone(&mut self, fund_id: FundingId) -> Result<()> {
+            self.ensure_not_paused()?;
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_campaign_succeeded(fund_id, &transaction)?;
+
+            let mut milestones = self.milestones.get(fund_id).unwrap_or_default();
+            let index = Self::current_milestone(&milestones).ok_or(Error::NoPendingMilestone)?;
+            let is_last = index + 1 == milestones.len();
+            let now = self.env().block_number();
+            let milestone = &mut milestones[index];
+            let approved = match milestone.status {
+                MilestoneStatus::Voting if now > milestone.voting_ends => {
+                    self.milestone_approved(fund_id, milestone)
+                }
+                MilestoneStatus::Voting => return Err(Error::VotingOpen),
+                _ if now > milestone.deadline => false,
+                _ => return Err(Error::NoVoteRunning),
+            };
+
+            if !approved {
+                milestone.status = MilestoneStatus::Rejected;
+                self.milestones.insert(fund_id, &milestones);
+                self.env().emit_event(MilestoneRejected {
+                    fund_id,
+                    milestone: index as u32,
+                });
+                return Ok(())
+            }
+
+            let escrow = self.current_funding.get(fund_id).unwrap_or(0);
+            let amount = if is_last {
+                escrow
+            } else {
+                core::cmp::min(milestone.amount, escrow)
+            };
+            milestone.status = MilestoneStatus::Released;
+            self.milestones.insert(fund_id, &milestones);
+            self.release_escrow(fund_id, transaction.currency, amount)?;
+
+            let fee = Self::platform_fee(amount, transaction.fee_bps)?;
+            self.pay_out(transaction.currency, transaction.beneficiary, amount - fee, fee)?;
+
+            transaction.released = transaction.released.saturating_add(amount);
+            transaction.withdrawn = is_last;
+            self.transactions.insert(fund_id, &transaction);
+            self.env().emit_event(MilestoneReleased {
+                fund_id,
+                milestone: index as u32,
+                amount: amount - fee,
+                fee,
+            });
+            Ok(())
+        }
+
+        /// Read how much `backer` has contributed to `fund_id`
+        #[ink(message)]
+        pub fn get_contribution(&self, fund_id: FundingId, backer: AccountId) -> Balance {
+            self.contributions.get((fund_id, backer)).unwrap_or(0)
+        }
+
+        /// Read the sum of all campaign escrows
+        #[ink(message)]
+        pub fn total_escrowed(&self) -> Balance {
+            self.escrowed(None)
+        }
+
+        #[ink(message)]
+        pub fn current_balance(&self) -> Balance {
+            self.env().balance()
+        }
+
+        #[ink(message)]
+        pub fn print(&self) {
+            let caller = self.env().caller();
+            ink_env::debug_println!("got a call from {:?}", caller);
+        }
+
+        /// Read the name of the contract
+        #[ink(message)]
+        pub fn name(&self) -> String {
+            self.name.clone()
+        }
+
+        /// Read the owner of the contract
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Read whether the contract is paused
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused.get(()).unwrap_or(false)
+        }
+
+        /// Stop campaigns from being created, funded or withdrawn.
+        ///
+        /// Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            let caller = self.ensure_owner()?;
+            self.paused.insert((), &true);
+            self.env().emit_event(Paused { by: caller });
+            Ok(())
+        }
+
+        /// Lift a previous `pause`.
+        ///
+        /// Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            let caller = self.ensure_owner()?;
+            self.paused.insert((), &false);
+            self.env().emit_event(Unpaused { by: caller });
+            Ok(())
+        }
+
+        /// Call off a fraudulent campaign so that its backers can ask for a refund.
+        ///
+        /// The creator's deposit is forfeited to the `treasury`. Can only be called
+        /// by the contract `owner`, and only before the campaign was withdrawn.
+        /// Campaigns carried over by `migrate` can not be cancelled, as their
+        /// backers could not be refunded.
+        #[ink(message)]
+        pub fn cancel_campaign(&mut self, fund_id: FundingId) -> Result<()> {
+            let caller = self.ensure_owner()?;
+            let mut transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_not_legacy(fund_id)?;
+            match self.campaign_state(fund_id, &transaction) {
+                CampaignState::Withdrawn => return Err(Error::AlreadyWithdrawn),
+                CampaignState::Cancelled => return Err(Error::CampaignCancelled),
+                _ => (),
+            }
+
+            transaction.cancelled = true;
+            self.transactions.insert(fund_id, &transaction);
+            if let Some(deposit) = self.deposits.get(fund_id) {
+                self.deposits.remove(fund_id);
+                self.transfer_out(None, self.treasury(), deposit.amount)?;
+            }
+            self.env().emit_event(CampaignCancelled { fund_id, by: caller });
+            Ok(())
+        }
+
+        /// Propose `new_owner` as the next owner of the contract.
+        ///
+        /// The handover only happens once `new_owner` calls `accept_ownership`.
+        /// Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn propose_owner(&mut self, new_owner: AccountId) -> Result<()> {
+            let caller = self.ensure_owner()?;
+            self.pending_owner.insert((), &new_owner);
+            self.env().emit_event(OwnershipProposed {
+                owner: caller,
+                proposed: new_owner,
+            });
+            Ok(())
+        }
+
+        /// Take over the contract after the current owner proposed the caller.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner() != Some(caller) {
+                return Err(Error::NotPendingOwner)
+            }
+            let previous = self.owner;
+            self.owner = caller;
+            self.pending_owner.remove(());
+            self.env().emit_event(OwnershipTransferred {
+                from: previous,
+                to: caller,
+            });
+            Ok(())
+        }
+
+        /// Read the platform fee new campaigns are created with, in basis points
+        #[ink(message)]
+        pub fn fee_bps(&self) -> u16 {
+            self.fee_bps.get(()).unwrap_or(0)
+        }
+
+        /// Read the account platform fees are paid to
+        #[ink(message)]
+        pub fn treasury(&self) -> AccountId {
+            self.treasury.get(()).unwrap_or(self.owner)
+        }
+
+        /// Change the platform fee for campaigns created from now on.
+        ///
+        /// Existing campaigns keep the fee they were created with.
+        /// Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn set_fee(&mut self, fee_bps: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if fee_bps > MAX_FEE_BPS {
+                return Err(Error::FeeTooHigh)
+            }
+            self.fee_bps.insert((), &fee_bps);
+            self.env().emit_event(FeeChanged { fee_bps });
+            Ok(())
+        }
+
+        /// Read the cancellation penalty new campaigns are created with, in basis points
+        #[ink(message)]
+        pub fn cancellation_penalty_bps(&self) -> u16 {
+            self.cancellation_penalty_bps.get(()).unwrap_or(0)
+        }
+
+        /// Change the share of the deposit that campaigns created from now on forfeit
+        /// when their creator cancels them.
+        ///
+        /// Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn set_cancellation_penalty(&mut self, penalty_bps: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if Balance::from(penalty_bps) > BPS_DENOMINATOR {
+                return Err(Error::PenaltyTooHigh)
+            }
+            self.cancellation_penalty_bps.insert((), &penalty_bps);
+            self.env().emit_event(CancellationPenaltyChanged { penalty_bps });
+            Ok(())
+        }
+
+        /// Change the account platform fees are paid to.
+        ///
+        /// Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn set_treasury(&mut self, treasury: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.treasury.insert((), &treasury);
+            self.env().emit_event(TreasuryChanged { treasury });
+            Ok(())
+        }
+
+        /// Read the account the owner proposed to hand the contract over to
+        #[ink(message)]
+        pub fn pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner.get(())
+        }
+
+        /// Read the storage layout version the stored state is in
+        #[ink(message)]
+        pub fn storage_version(&self) -> u32 {
+            self.version.get(()).unwrap_or(1)
+        }
+
+        /// Replace the code of the contract with the code uploaded under `code_hash`.
+        ///
+        /// Storage and escrowed balances stay where they are. If the new code comes
+        /// with a higher `STORAGE_VERSION`, the owner calls `migrate` on it until the
+        /// storage is converted. Can only be called by the contract `owner`.
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            self.ensure_owner()?;
+            ink_env::set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)?;
+            self.env().emit_event(Upgraded { code_hash });
+            Ok(())
+        }
+
+        /// Convert the next batch of campaigns to the current storage layout.
+        ///
+        /// Moves the campaigns of the first release out of `legacy_transactions`,
+        /// indexes them by creator and adds their escrow to the totals. Campaigns
+        /// the first release paid out are dropped together with the funding it left
+        /// behind for them. Returns whether the migration is complete, otherwise it
+        /// has to be called again. Can only be called by the contract `owner`.
+        ///
+        /// The first release did not record who paid in, so the backers of the
+        /// carried over campaigns can not be refunded. These campaigns can only be
+        /// funded to their goal and withdrawn; `cancel_funding` and `cancel_campaign`
+        /// refuse them.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<bool> {
+            self.ensure_owner()?;
+            let from = self.storage_version();
+            if from >= STORAGE_VERSION {
+                return Err(Error::NothingToMigrate)
+            }
+
+            let start = self.migration_cursor.get(()).unwrap_or(0);
+            let end = start
+                .saturating_add(MIGRATION_BATCH_SIZE)
+                .min(self.transaction_list.next_id);
+            for fund_id in start..end {
+                let legacy = match self.legacy_transactions.get(fund_id) {
+                    Some(legacy) => legacy,
+                    None => {
+                        self.current_funding.remove(fund_id);
+                        continue
+                    }
+                };
+                // The first release had no deadline and paid the creator out.
+                let transaction = Transaction {
+                    callee: legacy.callee,
+                    expected_value: legacy.expected_value,
+                    beneficiary: legacy.callee,
+                    pending_beneficiary: None,
+                    currency: None,
+                    deadline: BlockNumber::MAX,
+                    overflow_policy: OverflowPolicy::Reject,
+                    funding_model: FundingModel::AllOrNothing,
+                    fee_bps: 0,
+                    metadata: CampaignMetadata {
+                        title: String::new(),
+                        description: String::new(),
+                        content_hash: Vec::new(),
+                        category: Category::Other,
+                    },
+                    released: 0,
+                    withdrawn: false,
+                    cancelled: false,
+                };
+                self.transactions.insert(fund_id, &transaction);
+                self.legacy_transactions.remove(fund_id);
+                self.legacy_campaigns.insert(fund_id, &true);
+                self.index_by_creator(legacy.callee, fund_id);
+
+                let escrow = self.current_funding.get(fund_id).unwrap_or(0);
+                let total = self.escrowed(None).checked_add(escrow).ok_or(Error::Overflow)?;
+                self.total_escrowed.insert(None::<AccountId>, &total);
+            }
+            self.migration_cursor.insert((), &end);
+
+            if end < self.transaction_list.next_id {
+                return Ok(false)
+            }
+            self.transaction_list.transactions = Vec::new();
+            self.version.insert((), &STORAGE_VERSION);
+            self.migration_cursor.remove(());
+            self.env().emit_event(Migrated {
+                from,
+                to: STORAGE_VERSION,
+            });
+            Ok(true)
+        }
+
+        /// Take `value` as the caller's contribution to `fund_id`, claiming the reward
+        /// tier `tier_id` if one is given.
+        ///
+        /// Native payments arrive with the call, so the excess is sent back. Token
+        /// payments are pulled from the caller, so only the accepted part is taken.
+        fn contribute(
+            &mut self,
+            fund_id: FundingId,
+            tier_id: Option<u32>,
+            value: Balance,
+            in_token: bool,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.ensure_not_paused()?;
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            if transaction.currency.is_some() != in_token || (in_token && tier_id.is_some()) {
+                return Err(Error::WrongCurrency)
+            }
+            self.ensure_campaign_open(fund_id, &transaction)?;
+            if !self.is_allowed(fund_id, caller) {
+                return Err(Error::NotAllowed)
+            }
+            if value == 0 {
+                return Err(Error::ZeroContribution)
+            }
+            let limits = self.contribution_limits(fund_id);
+            if value < limits.min_contribution.unwrap_or(0) {
+                return Err(Error::ContributionTooSmall)
+            }
+            let funding = self.raised(fund_id, &transaction);
+
+            // Split the payment into the part we keep and the part we hand back.
+            let remaining = self
+                .funding_ceiling(fund_id, &transaction)
+                .map_or(Balance::MAX, |ceiling| ceiling.saturating_sub(funding));
+            let accepted = match transaction.overflow_policy {
+                OverflowPolicy::Reject if value > remaining => return Err(Error::GoalExceeded),
+                _ => core::cmp::min(value, remaining),
+            };
+            let excess = value - accepted;
+            let f = funding.checked_add(accepted).ok_or(Error::Overflow)?;
+
+            let previous = self.contributions.get((fund_id, caller));
+            let contributed = previous.unwrap_or(0).checked_add(accepted).ok_or(Error::Overflow)?;
+            if contributed > limits.max_per_backer.unwrap_or(Balance::MAX) {
+                return Err(Error::BackerCapExceeded)
+            }
+            let backers = self.backer_count(fund_id);
+            if previous.is_none() && backers >= limits.max_backers.unwrap_or(u32::MAX) {
+                return Err(Error::TooManyBackers)
+            }
+
+            if let Some(tier_id) = tier_id {
+                self.claim_tier(fund_id, caller, tier_id, accepted)?;
+            }
+            if let Some(token) = transaction.currency {
+                self.token_transfer_from(token, caller, accepted)?;
+            }
+            self.lock_escrow(fund_id, transaction.currency, accepted)?;
+            let f = f
+                .checked_add(self.accrue_match(fund_id, caller, &transaction, accepted, f)?)
+                .ok_or(Error::Overflow)?;
+            self.track_round_contribution(fund_id, caller, accepted)?;
+            self.contributions.insert((fund_id, caller), &contributed);
+            if previous.is_none() {
+                self.backer_count.insert(fund_id, &(backers + 1));
+            }
+            let total = self.total_contributions.get(fund_id).unwrap_or(0);
+            self.total_contributions.insert(fund_id, &total.saturating_add(accepted));
+            self.sync_receipt(fund_id, caller);
+
+            if excess > 0 && !in_token {
+                self.transfer_out(None, caller, excess)?;
+            }
+
+            self.env().emit_event(Contributed {
+                fund_id,
+                backer: caller,
+                amount: accepted,
+                total: f,
+            });
+            if funding < transaction.expected_value && f >= transaction.expected_value {
+                self.env().emit_event(GoalReached { fund_id, total: f });
+            }
+            for (index, goal) in self.get_stretch_goals(fund_id).iter().enumerate() {
+                if funding < goal.amount && f >= goal.amount {
+                    self.env().emit_event(StretchGoalUnlocked {
+                        fund_id,
+                        level: index as u32 + 1,
+                        amount: goal.amount,
+                    });
+                }
+            }
+            Ok(())
+        }
+
+        /// Match `accepted` from the pool attached to `fund_id`, if any, and return
+        /// the matched amount.
+        ///
+        /// Matches count toward the goal, so they stop at the goal unless the
+        /// campaign accepts going past it.
+        fn accrue_match(
+            &mut self,
+            fund_id: FundingId,
+            backer: AccountId,
+            transaction: &Transaction,
+            accepted: Balance,
+            funding: Balance,
+        ) -> Result<Balance> {
+            let pool_id = match self.campaign_pools.get(fund_id) {
+                Some(pool_id) => pool_id,
+                None => return Ok(0),
+            };
+            let mut pool = self.sponsor_pools.get(pool_id).ok_or(Error::UnknownPool)?;
+            let matched = self.pool_matches.get(fund_id).unwrap_or(0);
+
+            let mut amount = accepted
+                .checked_mul(Balance::from(pool.ratio_bps))
+                .ok_or(Error::Overflow)?
+                / BPS_DENOMINATOR;
+            amount = amount
+                .min(pool.cap_per_campaign.saturating_sub(matched))
+                .min(pool.available);
+            if let Some(ceiling) = self.funding_ceiling(fund_id, transaction) {
+                amount = amount.min(ceiling.saturating_sub(funding));
+            }
+            if amount == 0 {
+                return Ok(0)
+            }
+
+            pool.available -= amount;
+            self.sponsor_pools.insert(pool_id, &pool);
+            self.pool_matches.insert(fund_id, &(matched + amount));
+            let backer_matched = self.backer_matches.get((fund_id, backer)).unwrap_or(0);
+            self.backer_matches.insert((fund_id, backer), &(backer_matched + amount));
+            self.lock_escrow(fund_id, None, amount)?;
+            self.env().emit_event(Matched {
+                pool_id,
+                fund_id,
+                amount,
+            });
+            Ok(amount)
+        }
+
+        /// Bring the receipt of `backer`'s contribution to `fund_id` up to date.
+        ///
+        /// Mints a receipt for a new contribution and burns it once nothing is
+        /// contributed any more. A receipt that changed hands keeps recording the
+        /// backer's contribution and is burned from whoever holds it, so a backer
+        /// never has more than one receipt per campaign.
+        fn sync_receipt(&mut self, fund_id: FundingId, backer: AccountId) {
+            let amount = self.contributions.get((fund_id, backer)).unwrap_or(0);
+            let existing = self.backer_receipts.get((fund_id, backer));
+
+            if amount == 0 {
+                if let Some(receipt_id) = existing {
+                    let owner = self.receipt_owners.get(receipt_id);
+                    self.receipts.remove(receipt_id);
+                    self.receipt_owners.remove(receipt_id);
+                    self.backer_receipts.remove((fund_id, backer));
+                    if let Some(owner) = owner {
+                        self.receipt_balances.insert(owner, &self.balance_of(owner).saturating_sub(1));
+                    }
+                    self.receipt_supply.insert((), &self.total_supply().saturating_sub(1));
+                    self.env().emit_event(Transfer {
+                        from: owner,
+                        to: None,
+                        id: Id::U64(receipt_id),
+                    });
+                }
+                return
+            }
+
+            let receipt = Receipt {
+                fund_id,
+                amount,
+                tier_id: self.claimed_tiers.get((fund_id, backer)),
+            };
+            let receipt_id = match existing {
+                Some(receipt_id) => receipt_id,
+                None => {
+                    let receipt_id = self.next_receipt_id.get(()).unwrap_or(0);
+                    self.next_receipt_id.insert((), &(receipt_id + 1));
+                    self.receipt_owners.insert(receipt_id, &backer);
+                    self.receipt_balances.insert(backer, &(self.balance_of(backer) + 1));
+                    self.backer_receipts.insert((fund_id, backer), &receipt_id);
+                    self.receipt_supply.insert((), &(self.total_supply() + 1));
+                    self.env().emit_event(Transfer {
+                        from: None,
+                        to: Some(backer),
+                        id: Id::U64(receipt_id),
+                    });
+                    receipt_id
+                }
+            };
+            self.receipts.insert(receipt_id, &receipt);
+        }
+
+        /// Return the receipt id behind the PSP34 `id`, if it can be one.
+        fn receipt_id(id: &Id) -> Option<ReceiptId> {
+            match id {
+                Id::U64(receipt_id) => Some(*receipt_id),
+                _ => None,
+            }
+        }
+
+        /// Hand the match that `amount` of `backer`'s `contributed` earned back to the
+        /// pool of `fund_id`.
+        fn reverse_match(
+            &mut self,
+            fund_id: FundingId,
+            backer: AccountId,
+            amount: Balance,
+            contributed: Balance,
+        ) -> Result<()> {
+            let backer_matched = self.backer_matches.get((fund_id, backer)).unwrap_or(0);
+            let pool_id = match self.campaign_pools.get(fund_id) {
+                Some(pool_id) if backer_matched > 0 => pool_id,
+                _ => return Ok(()),
+            };
+            let mut pool = self.sponsor_pools.get(pool_id).ok_or(Error::UnknownPool)?;
+            // `amount <= contributed`, so the share never exceeds the backer's match.
+            let share = backer_matched.checked_mul(amount).ok_or(Error::Overflow)? / contributed;
+
+            self.release_escrow(fund_id, None, share)?;
+            pool.available = pool.available.checked_add(share).ok_or(Error::Overflow)?;
+            self.sponsor_pools.insert(pool_id, &pool);
+            let matched = self.pool_matches.get(fund_id).unwrap_or(0);
+            self.pool_matches.insert(fund_id, &matched.saturating_sub(share));
+            self.backer_matches.insert((fund_id, backer), &(backer_matched - share));
+            Ok(())
+        }
+
+        /// Stop counting up to `amount` from `backer` toward the round of `fund_id`,
+        /// unless the round was finalized already.
+        fn untrack_round_contribution(&mut self, fund_id: FundingId, backer: AccountId, amount: Balance) -> Result<()> {
+            match self.campaign_rounds.get(fund_id).and_then(|round_id| self.rounds.get(round_id)) {
+                Some(round) if !round.finalized => (),
+                _ => return Ok(()),
+            }
+            let previous = self.round_contributions.get((fund_id, backer)).unwrap_or(0);
+            if previous == 0 {
+                return Ok(())
+            }
+            let current = previous.saturating_sub(amount);
+            let sqrt_sum = self.round_sqrt_sums.get(fund_id).unwrap_or(0);
+            let sqrt_sum = sqrt_sum - Self::scaled_sqrt(previous) + Self::scaled_sqrt(current);
+            self.round_contributions.insert((fund_id, backer), &current);
+            self.round_sqrt_sums.insert(fund_id, &sqrt_sum);
+            Ok(())
+        }
+
+        /// Count `amount` from `backer` toward the round of `fund_id`, if it is running.
+        fn track_round_contribution(&mut self, fund_id: FundingId, backer: AccountId, amount: Balance) -> Result<()> {
+            let round = match self.campaign_rounds.get(fund_id).and_then(|round_id| self.rounds.get(round_id)) {
+                Some(round) => round,
+                None => return Ok(()),
+            };
+            let now = self.env().block_number();
+            if now < round.start || now > round.end {
+                return Ok(())
+            }
+
+            let previous = self.round_contributions.get((fund_id, backer)).unwrap_or(0);
+            let current = previous.checked_add(amount).ok_or(Error::Overflow)?;
+            let sqrt_sum = self.round_sqrt_sums.get(fund_id).unwrap_or(0);
+            let sqrt_sum = sqrt_sum - Self::scaled_sqrt(previous);
+            let sqrt_sum = sqrt_sum
+                .checked_add(Self::scaled_sqrt(current))
+                .ok_or(Error::Overflow)?;
+            self.round_contributions.insert((fund_id, backer), &current);
+            self.round_sqrt_sums.insert(fund_id, &sqrt_sum);
+            Ok(())
+        }
+
+        /// Return the square root of `value`, scaled by `SQRT_SCALE`.
+        fn scaled_sqrt(value: Balance) -> Balance {
+            match value.checked_mul(SQRT_SCALE * SQRT_SCALE) {
+                Some(scaled) => Self::isqrt(scaled),
+                // This far up the fraction the scale keeps no longer matters.
+                None => Self::isqrt(value) * SQRT_SCALE,
+            }
+        }
+
+        /// Score each campaign of `round` by the square of the sum of the square
+        /// roots of its round contributions, along with how much it can still raise.
+        ///
+        /// Returns the scores and their total, or `None` if they do not fit a `Balance`.
+        #[allow(clippy::type_complexity)]
+        fn round_scores(&self, round: &Round) -> Option<(Vec<(FundingId, Balance, Option<Balance>)>, Balance)> {
+            let mut scores = Vec::with_capacity(round.campaigns.len());
+            let mut total_score: Balance = 0;
+            for &fund_id in &round.campaigns {
+                let transaction = match self.transactions.get(fund_id) {
+                    Some(transaction) => transaction,
+                    None => continue,
+                };
+                let score = match self.campaign_state(fund_id, &transaction) {
+                    CampaignState::Open | CampaignState::Succeeded => {
+                        // Back in balance units, so that scores grow like contributions.
+                        let sqrt_sum = self.round_sqrt_sums.get(fund_id).unwrap_or(0);
+                        Self::mul_div(sqrt_sum, sqrt_sum, SQRT_SCALE * SQRT_SCALE)?
+                    }
+                    _ => 0,
+                };
+                let room = self
+                    .funding_ceiling(fund_id, &transaction)
+                    .map(|ceiling| ceiling.saturating_sub(self.raised(fund_id, &transaction)));
+                total_score = total_score.checked_add(score)?;
+                scores.push((fund_id, score, room));
+            }
+            Some((scores, total_score))
+        }
+
+        /// Return `a * b / c` rounded down, or `None` if `c` is zero or the result
+        /// does not fit a `Balance`. The product is taken in 256 bits.
+        fn mul_div(a: Balance, b: Balance, c: Balance) -> Option<Balance> {
+            const LOW: Balance = u64::MAX as Balance;
+            let (a_high, a_low) = (a >> 64, a & LOW);
+            let (b_high, b_low) = (b >> 64, b & LOW);
+            let low_low = a_low * b_low;
+            let high_low = a_high * b_low;
+            let low_high = a_low * b_high;
+            let middle = (low_low >> 64) + (high_low & LOW) + (low_high & LOW);
+            let low = (low_low & LOW) | (middle << 64);
+            let high = a_high * b_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+            if high == 0 {
+                return low.checked_div(c)
+            }
+            if high >= c {
+                return None
+            }
+
+            // Long division, one bit of `low` at a time. The remainder stays below
+            // `c`, so only the bit shifted out on top has to be carried along.
+            let mut remainder = high;
+            let mut quotient: Balance = 0;
+            for bit in (0..128).rev() {
+                let carry = remainder >> 127;
+                remainder = (remainder << 1) | ((low >> bit) & 1);
+                quotient <<= 1;
+                if carry == 1 || remainder >= c {
+                    remainder = remainder.wrapping_sub(c);
+                    quotient |= 1;
+                }
+            }
+            Some(quotient)
+        }
+
+        /// Return the largest integer whose square does not exceed `n`.
+        fn isqrt(n: Balance) -> Balance {
+            if n < 2 {
+                return n
+            }
+            // Newton's method, approaching the root from above.
+            let mut x = n;
+            let mut y = n / 2 + n % 2;
+            while y < x {
+                x = y;
+                y = (x + n / x) / 2;
+            }
+            x
+        }
+
+        /// Release what is left of the `matched` part of the escrow of `fund_id`, once
+        /// its backers are sure to get their contributions back, and return it.
+        fn take_unspent_match(&mut self, fund_id: FundingId, matched: Balance) -> Result<Balance> {
+            let escrow = self.current_funding.get(fund_id).unwrap_or(0);
+            let owed = self.total_contributions.get(fund_id).unwrap_or(0);
+            let unspent = core::cmp::min(matched, escrow.saturating_sub(owed));
+            if unspent > 0 {
+                self.release_escrow(fund_id, None, unspent)?;
+            }
+            Ok(unspent)
+        }
+
+        /// Return the pool `pool_id` if the caller is its sponsor, or an error otherwise.
+        fn ensure_sponsor(&self, pool_id: PoolId) -> Result<SponsorPool> {
+            let pool = self.sponsor_pools.get(pool_id).ok_or(Error::UnknownPool)?;
+            if pool.sponsor != self.env().caller() {
+                return Err(Error::NotSponsor)
+            }
+            Ok(pool)
+        }
+
+        /// Let `accounts` back `fund_id` if `allowed`, or stop them otherwise.
+        fn update_allowlist(&mut self, fund_id: FundingId, accounts: Vec<AccountId>, allowed: bool) -> Result<()> {
+            let transaction = self.ensure_transaction_exists(fund_id)?;
+            self.ensure_transaction_owner(&transaction, self.env().caller())?;
+            if accounts.len() > MAX_ALLOWLIST_BATCH {
+                return Err(Error::BatchTooLarge)
+            }
+            for account in accounts.iter() {
+                if allowed {
+                    self.allowlist.insert((fund_id, account), &true);
+                } else {
+                    self.allowlist.remove((fund_id, account));
+                }
+            }
+            self.env().emit_event(AllowlistUpdated {
+                fund_id,
+                accounts,
+                allowed,
+            });
+            Ok(())
+        }
+
+        /// Return the Merkle leaf of `account`.
+        fn merkle_leaf(account: &AccountId) -> Hash {
+            let mut output = [0u8; 32];
+            ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(account, &mut output);
+            Hash::from(output)
+        }
+
+        /// Return the root reached by hashing `leaf` up the tree along `proof`.
+        fn merkle_root(leaf: Hash, proof: &[Hash]) -> Hash {
+            proof.iter().fold(leaf, |node, sibling| {
+                let (left, right) = if node.as_ref() <= sibling.as_ref() {
+                    (node, *sibling)
+                } else {
+                    (*sibling, node)
+                };
+                let mut output = [0u8; 32];
+                ink_env::hash_encoded::<ink_env::hash::Blake2x256, _>(&(left, right), &mut output);
+                Hash::from(output)
+            })
+        }
+
+        /// Claim a slot of the reward tier `tier_id` of `fund_id` for `backer`'s `pledge`.
+        fn claim_tier(&mut self, fund_id: FundingId, backer: AccountId, tier_id: u32, pledge: Balance) -> Result<()> {
+            if self.claimed_tiers.get((fund_id, backer)).is_some() {
+                return Err(Error::TierAlreadyClaimed)
+            }
+            let mut tiers = self.reward_tiers.get(fund_id).unwrap_or_default();
+            let tier = tiers.get_mut(tier_id as usize).ok_or(Error::UnknownTier)?;
+            if pledge < tier.min_pledge {
+                return Err(Error::PledgeBelowTierMinimum)
+            }
+            if tier.claimed >= tier.max_slots {
+                return Err(Error::TierSoldOut)
+            }
+
+            tier.claimed += 1;
+            self.reward_tiers.insert(fund_id, &tiers);
+            self.claimed_tiers.insert((fund_id, backer), &tier_id);
+            self.env().emit_event(TierClaimed {
+                fund_id,
+                backer,
+                tier_id,
+            });
+            Ok(())
+        }
+
+        /// Give the reward tier slot `backer` claimed on `fund_id`, if any, back.
+        fn release_tier(&mut self, fund_id: FundingId, backer: AccountId) {
+            if let Some(tier_id) = self.claimed_tiers.get((fund_id, backer)) {
+                let mut tiers = self.reward_tiers.get(fund_id).unwrap_or_default();
+                if let Some(tier) = tiers.get_mut(tier_id as usize) {
+                    tier.claimed = tier.claimed.saturating_sub(1);
+                    self.reward_tiers.insert(fund_id, &tiers);
+                }
+                self.claimed_tiers.remove((fund_id, backer));
+            }
+        }
+
+        /// Add `amount` of `currency` to the escrow of `fund_id`.
+        fn lock_escrow(&mut self, fund_id: FundingId, currency: Option<AccountId>, amount: Balance) -> Result<()> {
+            let funding = self.current_funding.get(fund_id).unwrap_or(0);
+            let funding = funding.checked_add(amount).ok_or(Error::Overflow)?;
+            let total = self.escrowed(currency).checked_add(amount).ok_or(Error::Overflow)?;
+            self.total_escrowed.insert(currency, &total);
+            self.current_funding.insert(fund_id, &funding);
+            Ok(())
+        }
+
+        /// Take `amount` of `currency` out of the escrow of `fund_id` before paying it out.
+        ///
+        /// Fails if the campaign's own escrow, the escrow total or the contract
+        /// balance can not cover it. Token balances are checked by the token itself
+        /// on transfer.
+        fn release_escrow(&mut self, fund_id: FundingId, currency: Option<AccountId>, amount: Balance) -> Result<()> {
+            let funding = self.current_funding.get(fund_id).unwrap_or(0);
+            let funding = funding.checked_sub(amount).ok_or(Error::InsufficientEscrow)?;
+            let total = self.escrowed(currency);
+            if currency.is_none() && total > self.env().balance() {
+                return Err(Error::InsufficientContractBalance)
+            }
+            let total = total.checked_sub(amount).ok_or(Error::InsufficientEscrow)?;
+            self.total_escrowed.insert(currency, &total);
+            self.current_funding.insert(fund_id, &funding);
+            Ok(())
+        }
+
+        /// Compute the platform fee on `amount` at `fee_bps` basis points.
+        fn platform_fee(amount: Balance, fee_bps: u16) -> Result<Balance> {
+            let fee = amount
+                .checked_mul(Balance::from(fee_bps))
+                .ok_or(Error::Overflow)?;
+            Ok(fee / BPS_DENOMINATOR)
+        }
+
+        /// Transfer `amount` of `currency` to `to` and `fee` to the `treasury`.
+        fn pay_out(&mut self, currency: Option<AccountId>, to: AccountId, amount: Balance, fee: Balance) -> Result<()> {
+            if fee > 0 {
+                self.transfer_out(currency, self.treasury(), fee)?;
+            }
+            self.transfer_out(currency, to, amount)
+        }
+
+        /// Transfer `amount` of `currency` out of the contract to `to`.
+        ///
+        /// A native transfer can fail if it would have brought the contract's balance
+        /// below minimum balance.
+        fn transfer_out(&mut self, currency: Option<AccountId>, to: AccountId, amount: Balance) -> Result<()> {
+            match currency {
+                None => self
+                    .env()
+                    .transfer(to, amount)
+                    .map_err(|_| Error::TransferFailed),
+                Some(token) => self.token_transfer(token, to, amount),
+            }
+        }
+
+        /// Send `amount` of the PSP22 `token` held by the contract to `to`.
+        ///
+        /// The off-chain environment can not call other contracts, so the unit tests
+        /// do not cover this or `token_transfer_from`.
+        fn token_transfer(&mut self, token: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            use ink_env::call::{build_call, Call, ExecutionInput, Selector};
+
+            build_call::<ink_env::DefaultEnvironment>()
+                .call_type(Call::new().callee(token).gas_limit(0))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), PSP22Error>>()
+                .fire()
+                .map_err(|_| Error::TokenTransferFailed)?
+                .map_err(|_| Error::TokenTransferFailed)
+        }
+
+        /// Pull `amount` of the PSP22 `token` from `from` into the contract.
+        ///
+        /// `from` has to have approved the contract for at least `amount`.
+        fn token_transfer_from(&mut self, token: AccountId, from: AccountId, amount: Balance) -> Result<()> {
+            use ink_env::call::{build_call, Call, ExecutionInput, Selector};
+
+            build_call::<ink_env::DefaultEnvironment>()
+                .call_type(Call::new().callee(token).gas_limit(0))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER_FROM_SELECTOR))
+                        .push_arg(from)
+                        .push_arg(self.env().account_id())
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), PSP22Error>>()
+                .fire()
+                .map_err(|_| Error::TokenTransferFailed)?
+                .map_err(|_| Error::TokenTransferFailed)
+        }
+
+        /// Return the caller or an error if it is not the contract `owner`.
+        fn ensure_owner(&self) -> Result<AccountId> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(caller)
+        }
+
+        /// Return an error if the contract is paused or its storage not yet migrated.
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.is_paused() {
+                return Err(Error::ContractPaused)
+            }
+            self.ensure_migrated()
+        }
+
+        /// Return an error if the storage is not yet migrated to `STORAGE_VERSION`.
+        fn ensure_migrated(&self) -> Result<()> {
+            if self.storage_version() < STORAGE_VERSION {
+                return Err(Error::MigrationPending)
+            }
+            Ok(())
+        }
+
+        /// Return an error if `fund_id` was carried over from the first release.
+        fn ensure_not_legacy(&self, fund_id: FundingId) -> Result<()> {
+            if self.legacy_campaigns.get(fund_id).unwrap_or(false) {
+                return Err(Error::LegacyCampaign)
+            }
+            Ok(())
+        }
+
+        /// Read the sum of all campaign escrows in `currency`.
+        fn escrowed(&self, currency: Option<AccountId>) -> Balance {
+            self.total_escrowed.get(currency).unwrap_or(0)
+        }
+
+        /// Add `fund_id` to the campaigns listed for `creator`.
+        fn index_by_creator(&mut self, creator: AccountId, fund_id: FundingId) {
+            let count = self.creator_count.get(creator).unwrap_or(0);
+            self.by_creator.insert((creator, count), &fund_id);
+            self.creator_count.insert(creator, &(count + 1));
+        }
+
+        /// Return an error if `metadata` breaks any of the length limits.
+        fn ensure_valid_metadata(metadata: &CampaignMetadata) -> Result<()> {
+            if metadata.title.is_empty() || metadata.title.len() > MAX_TITLE_LEN {
+                return Err(Error::InvalidTitle)
+            }
+            if metadata.description.len() > MAX_DESCRIPTION_LEN {
+                return Err(Error::DescriptionTooLong)
+            }
+            if metadata.content_hash.len() > MAX_CONTENT_HASH_LEN {
+                return Err(Error::ContentHashTooLong)
+            }
+            Ok(())
+        }
+
+        /// Return the transaction `fund_id` or an error if it does not exist.
+        fn ensure_transaction_exists(&self, fund_id: FundingId) -> Result<Transaction> {
+            self.transactions.get(fund_id).ok_or(Error::UnknownCampaign)
+        }
+
+        /// Return an error if `caller_id` did not create the `transaction`.
+        fn ensure_transaction_owner(&self, transaction: &Transaction, caller_id: AccountId) -> Result<()> {
+            if transaction.callee != caller_id {
+                return Err(Error::NotCampaignOwner)
+            }
+            Ok(())
+        }
+
+        /// Return an error if `caller_id` is neither the creator nor the beneficiary of the `transaction`.
+        fn ensure_transaction_manager(&self, transaction: &Transaction, caller_id: AccountId) -> Result<()> {
+            if transaction.callee != caller_id && transaction.beneficiary != caller_id {
+                return Err(Error::NotCampaignOwner)
+            }
+            Ok(())
+        }
+
+        /// Return an error if the transaction `fund_id` does not accept funding.
+        ///
+        /// Campaigns that accept overfunding stay fundable after reaching their goal
+        /// until the deadline passes.
+        fn ensure_campaign_open(&self, fund_id: FundingId, transaction: &Transaction) -> Result<()> {
+            match self.campaign_state(fund_id, transaction) {
+                CampaignState::Open => Ok(()),
+                CampaignState::Succeeded if self.env().block_number() <= transaction.deadline => {
+                    match self.hard_caps.get(fund_id) {
+                        Some(hard_cap) if self.raised(fund_id, transaction) < hard_cap => Ok(()),
+                        Some(_) => Err(Error::HardCapReached),
+                        None if transaction.overflow_policy == OverflowPolicy::Accept => Ok(()),
+                        None => Err(Error::GoalAlreadyReached),
+                    }
+                }
+                CampaignState::Succeeded => Err(Error::GoalAlreadyReached),
+                CampaignState::Failed => Err(Error::DeadlinePassed),
+                CampaignState::Withdrawn => Err(Error::AlreadyWithdrawn),
+                CampaignState::Cancelled => Err(Error::CampaignCancelled),
+            }
+        }
+
+        /// Return an error if the transaction `fund_id` can not be withdrawn.
+        fn ensure_campaign_succeeded(&self, fund_id: FundingId, transaction: &Transaction) -> Result<()> {
+            match self.campaign_state(fund_id, transaction) {
+                CampaignState::Succeeded => Ok(()),
+                CampaignState::Open | CampaignState::Failed => Err(Error::GoalNotReached),
+                CampaignState::Withdrawn => Err(Error::AlreadyWithdrawn),
+                CampaignState::Cancelled => Err(Error::CampaignCancelled),
+            }
+        }
+
+        /// Return an error if the transaction `fund_id` can not be withdrawn from.
+        ///
+        /// Keep-it-all campaigns can be withdrawn from while they are open.
+        fn ensure_campaign_withdrawable(&self, fund_id: FundingId, transaction: &Transaction) -> Result<()> {
+            match self.campaign_state(fund_id, transaction) {
+                CampaignState::Open if transaction.funding_model == FundingModel::KeepItAll => Ok(()),
+                _ => self.ensure_campaign_succeeded(fund_id, transaction),
+            }
+        }
+
+        /// Return an error if the backers of `fund_id` can not ask for a refund.
+        fn ensure_refundable(&self, fund_id: FundingId, transaction: &Transaction) -> Result<()> {
+            match self.campaign_state(fund_id, transaction) {
+                CampaignState::Failed | CampaignState::Cancelled => Ok(()),
+                _ => Err(Error::NotRefundable),
+            }
+        }
+
+        /// Return the index of the first milestone that was neither released nor rejected.
+        fn current_milestone(milestones: &[Milestone]) -> Option<usize> {
+            milestones.iter().position(|milestone| {
+                milestone.status == MilestoneStatus::Pending
+                    || milestone.status == MilestoneStatus::Voting
+            })
+        }
+
+        /// Return whether the finished vote on `milestone` of `fund_id` approved its
+        /// release: the approvals have to reach the quorum and outweigh the rejections.
+        fn milestone_approved(&self, fund_id: FundingId, milestone: &Milestone) -> bool {
+            let contributions = self.total_contributions.get(fund_id).unwrap_or(0);
+            let quorum = Self::mul_div(contributions, MILESTONE_QUORUM_BPS, BPS_DENOMINATOR)
+                .unwrap_or(contributions);
+            milestone.approvals > 0
+                && milestone.approvals >= quorum
+                && milestone.rejections <= milestone.approvals
+        }
+
+        /// Return whether backers rejected a milestone of `fund_id`.
+        fn milestone_rejected(&self, fund_id: FundingId) -> bool {
+            self.milestones
+                .get(fund_id)
+                .unwrap_or_default()
+                .iter()
+                .any(|milestone| milestone.status == MilestoneStatus::Rejected)
+        }
+
+        /// Return everything `transaction` raised: its escrow plus what it already paid out.
+        fn raised(&self, fund_id: FundingId, transaction: &Transaction) -> Balance {
+            self.current_funding
+                .get(fund_id)
+                .unwrap_or(0)
+                .saturating_add(transaction.released)
+        }
+
+        /// Return the most `fund_id` raises before contributions count as overflow,
+        /// or `None` if it takes whatever comes in.
+        fn funding_ceiling(&self, fund_id: FundingId, transaction: &Transaction) -> Option<Balance> {
+            match self.hard_caps.get(fund_id) {
+                Some(hard_cap) => Some(hard_cap),
+                None if transaction.overflow_policy == OverflowPolicy::Accept => None,
+                None => Some(transaction.expected_value),
+            }
+        }
+
+        /// Return whether `fund_id` would still take funding.
+        fn accepts_funding(&self, fund_id: FundingId, transaction: &Transaction) -> bool {
+            self.ensure_campaign_open(fund_id, transaction).is_ok()
+        }
+
+        /// Build the listing summary of the transaction `fund_id`.
+        fn campaign_summary(&self, fund_id: FundingId) -> Option<CampaignSummary> {
+            let transaction = self.transactions.get(fund_id)?;
+            Some(CampaignSummary {
+                title: transaction.metadata.title.clone(),
+                category: transaction.metadata.category,
+                creator: transaction.callee,
+                expected_value: transaction.expected_value,
+                current_funding: self.current_funding.get(fund_id).unwrap_or(0),
+                deadline: transaction.deadline,
+                state: self.campaign_state(fund_id, &transaction),
+            })
+        }
+
+        /// Derive the lifecycle state of `transaction` from its funding and deadline.
+        fn campaign_state(&self, fund_id: FundingId, transaction: &Transaction) -> CampaignState {
+            if transaction.cancelled {
+                return CampaignState::Cancelled
+            }
+            if transaction.withdrawn {
+                return CampaignState::Withdrawn
+            }
+            if self.milestone_rejected(fund_id) {
+                return CampaignState::Failed
+            }
+            let raised = self.raised(fund_id, transaction);
+            if raised >= transaction.expected_value {
+                CampaignState::Succeeded
+            } else if self.env().block_number() <= transaction.deadline {
+                CampaignState::Open
+            } else if transaction.funding_model == FundingModel::KeepItAll && raised > 0 {
+                CampaignState::Succeeded
+            } else {
+                CampaignState::Failed
+            }
+        }
+    }
+
+    /// Unit tests for the contract, run against the off-chain environment.
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        type Environment = ink_env::DefaultEnvironment;
+        type Event = <Fundraiser as ink_lang::reflect::ContractEventBase>::Type;
+
+        const GOAL: Balance = 100;
+        const DEADLINE: BlockNumber = 10;
+        const FEE_BPS: u16 = 500;
+
+        fn default_accounts() -> ink_env::test::DefaultAccounts<Environment> {
+            ink_env::test::default_accounts::<Environment>()
+        }
+
+        fn set_caller(caller: AccountId) {
+            ink_env::test::set_caller::<Environment>(caller);
+        }
+
+        fn contract_id() -> AccountId {
+            ink_env::test::callee::<Environment>()
+        }
+
+        fn set_balance(account: AccountId, balance: Balance) {
+            ink_env::test::set_account_balance::<Environment>(account, balance)
+        }
+
+        fn get_balance(account: AccountId) -> Balance {
+            ink_env::test::get_account_balance::<Environment>(account)
+                .expect("Cannot get account balance")
+        }
+
+        fn advance_blocks(count: BlockNumber) {
+            for _ in 0..count {
+                ink_env::test::advance_block::<Environment>();
+            }
+        }
+
+        /// Deploy the contract as `alice` and give every other account something to spend.
+        ///
+        /// The off-chain environment runs the contract under `alice`'s account, so
+        /// `alice` never takes part in a campaign.
+        fn setup() -> Fundraiser {
+            setup_with_fee(0)
+        }
+
+        /// Like `setup`, but with a platform fee paid to `frank`.
+        fn setup_with_fee(fee_bps: u16) -> Fundraiser {
+            let accounts = default_accounts();
+            for account in [accounts.bob, accounts.charlie, accounts.django, accounts.eve] {
+                set_balance(account, 1_000);
+            }
+            set_balance(accounts.frank, 0);
+            set_balance(contract_id(), 0);
+            set_caller(accounts.alice);
+            Fundraiser::new(String::from("fundraiser"), accounts.alice, fee_bps, accounts.frank)
+        }
+
+        fn metadata() -> CampaignMetadata {
+            CampaignMetadata {
+                title: String::from("Community garden"),
+                description: String::from("Raised beds for the whole street."),
+                content_hash: Vec::from(&b"bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"[..]),
+                category: Category::Community,
+            }
+        }
+
+        fn create_as(
+            contract: &mut Fundraiser,
+            creator: AccountId,
+            policy: OverflowPolicy,
+        ) -> FundingId {
+            create_with_model(contract, creator, policy, FundingModel::AllOrNothing)
+        }
+
+        fn create_with_model(
+            contract: &mut Fundraiser,
+            creator: AccountId,
+            policy: OverflowPolicy,
+            model: FundingModel,
+        ) -> FundingId {
+            set_caller(creator);
+            contract
+                .create_a_funding(GOAL, DEADLINE, policy, model, metadata(), creator, None)
+                .expect("campaign creation failed")
+        }
+
+        /// Make a payable `call` as `sender` with `value` attached, moving the balance
+        /// like the chain would.
+        ///
+        /// The transfer is rolled back if the message fails, just like a reverted call.
+        fn call_with_value<T>(
+            sender: AccountId,
+            value: Balance,
+            call: impl FnOnce() -> Result<T>,
+        ) -> Result<T> {
+            set_caller(sender);
+            set_balance(sender, get_balance(sender) - value);
+            set_balance(contract_id(), get_balance(contract_id()) + value);
+            ink_env::test::set_value_transferred::<Environment>(value);
+            let result = call();
+            if result.is_err() {
+                set_balance(sender, get_balance(sender) + value);
+                set_balance(contract_id(), get_balance(contract_id()) - value);
+            }
+            ink_env::test::set_value_transferred::<Environment>(0);
+            result
+        }
+
+        /// Pay `value` into `fund_id` as `backer`.
+        fn fund_as(
+            contract: &mut Fundraiser,
+            backer: AccountId,
+            fund_id: FundingId,
+            value: Balance,
+        ) -> Result<()> {
+            call_with_value(backer, value, || contract.fund(fund_id))
+        }
+
+        /// Pay `value` into `fund_id` as `backer`, claiming the reward tier `tier_id`.
+        fn fund_tier_as(
+            contract: &mut Fundraiser,
+            backer: AccountId,
+            fund_id: FundingId,
+            tier_id: u32,
+            value: Balance,
+        ) -> Result<()> {
+            call_with_value(backer, value, || contract.fund_tier(fund_id, tier_id))
+        }
+
+        fn emitted_events() -> Vec<Event> {
+            ink_env::test::recorded_events()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect()
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = default_accounts();
+            let contract = setup();
+            assert_eq!(contract.name(), String::from("fundraiser"));
+            assert_eq!(contract.owner(), accounts.alice);
+            assert!(!contract.is_paused());
+            assert!(contract.get_funding(0).is_none());
+            assert_eq!(contract.get_campaign_state(0), None);
+            assert_eq!(contract.total_escrowed(), 0);
+        }
+
+        #[ink::test]
+        fn create_a_funding_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+
+            assert_eq!(create_as(&mut contract, accounts.bob, OverflowPolicy::Reject), 0);
+            assert_eq!(create_as(&mut contract, accounts.charlie, OverflowPolicy::Accept), 1);
+
+            let transaction = contract.get_funding(0).unwrap();
+            assert_eq!(transaction.callee, accounts.bob);
+            assert_eq!(transaction.expected_value, GOAL);
+            assert_eq!(transaction.deadline, DEADLINE);
+            assert_eq!(transaction.overflow_policy, OverflowPolicy::Reject);
+            assert_eq!(contract.get_funding(1).unwrap().callee, accounts.charlie);
+            assert_eq!(contract.get_campaign_state(0), Some(CampaignState::Open));
+            assert!(matches!(
+                emitted_events()[..],
+                [Event::CampaignCreated(_), Event::CampaignCreated(_)]
+            ));
+        }
+
+        #[ink::test]
+        fn create_a_funding_with_past_deadline_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            advance_blocks(2);
+            assert_eq!(
+                contract.create_a_funding(GOAL, 1, OverflowPolicy::Reject, FundingModel::AllOrNothing, metadata(), accounts.bob, None),
+                Err(Error::InvalidDeadline)
+            );
+        }
+
+        #[ink::test]
+        fn create_a_funding_enforces_metadata_limits() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let mut invalid = metadata();
+            invalid.title = String::new();
+            assert_eq!(
+                contract.create_a_funding(GOAL, DEADLINE, OverflowPolicy::Reject, FundingModel::AllOrNothing, invalid, accounts.bob, None),
+                Err(Error::InvalidTitle)
+            );
+
+            let mut invalid = metadata();
+            invalid.description = "x".repeat(MAX_DESCRIPTION_LEN + 1);
+            assert_eq!(
+                contract.create_a_funding(GOAL, DEADLINE, OverflowPolicy::Reject, FundingModel::AllOrNothing, invalid, accounts.bob, None),
+                Err(Error::DescriptionTooLong)
+            );
+
+            let mut invalid = metadata();
+            invalid.content_hash = vec![0; MAX_CONTENT_HASH_LEN + 1];
+            assert_eq!(
+                contract.create_a_funding(GOAL, DEADLINE, OverflowPolicy::Reject, FundingModel::AllOrNothing, invalid, accounts.bob, None),
+                Err(Error::ContentHashTooLong)
+            );
+            assert_eq!(contract.campaign_count(), 0);
+        }
+
+        #[ink::test]
+        fn update_metadata_works_while_open() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let mut updated = metadata();
+            updated.title = String::from("Community orchard");
+            updated.category = Category::Charity;
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.update_metadata(fund_id, updated.clone()),
+                Err(Error::NotCampaignOwner)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(contract.update_metadata(fund_id, updated.clone()), Ok(()));
+            assert_eq!(contract.get_funding(fund_id).unwrap().metadata, updated);
+            assert_eq!(contract.list_campaigns(0, 1)[0].1.title, updated.title);
+
+            advance_blocks(DEADLINE + 1);
+            assert_eq!(
+                contract.update_metadata(fund_id, metadata()),
+                Err(Error::DeadlinePassed)
+            );
+        }
+
+        #[ink::test]
+        fn list_campaigns_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            for _ in 0..3 {
+                create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            }
+            create_as(&mut contract, accounts.charlie, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.django, 1, 40), Ok(()));
+
+            assert_eq!(contract.campaign_count(), 4);
+            let page = contract.list_campaigns(1, 2);
+            assert_eq!(page.len(), 2);
+            assert_eq!(page[0].0, 1);
+            assert_eq!(page[0].1.current_funding, 40);
+            assert_eq!(page[0].1.state, CampaignState::Open);
+            assert_eq!(page[1].0, 2);
+            assert_eq!(contract.list_campaigns(3, 10).len(), 1);
+            assert!(contract.list_campaigns(4, 10).is_empty());
+            assert!(contract.list_campaigns(u32::MAX, u32::MAX).is_empty());
+        }
+
+        #[ink::test]
+        fn campaigns_by_creator_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            create_as(&mut contract, accounts.charlie, OverflowPolicy::Reject);
+            create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            let ids: Vec<FundingId> = contract
+                .campaigns_by_creator(accounts.bob, 0, 10)
+                .into_iter()
+                .map(|(fund_id, _)| fund_id)
+                .collect();
+            assert_eq!(ids, [0, 2]);
+            assert_eq!(contract.campaigns_by_creator(accounts.bob, 1, 1)[0].0, 2);
+            assert_eq!(contract.campaigns_by_creator(accounts.charlie, 0, 10)[0].1.creator, accounts.charlie);
+            assert!(contract.campaigns_by_creator(accounts.django, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn fund_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 20), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+
+            assert_eq!(contract.get_funding_status(fund_id), Some(60));
+            assert_eq!(contract.get_contribution(fund_id, accounts.charlie), 40);
+            assert_eq!(contract.get_contribution(fund_id, accounts.django), 20);
+            assert_eq!(contract.total_escrowed(), 60);
+            assert_eq!(get_balance(contract_id()), 60);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Open));
+        }
+
+        #[ink::test]
+        fn fund_reaching_goal_emits_goal_reached() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Succeeded));
+            let events = emitted_events();
+            // CampaignCreated, the receipt's Transfer, Contributed and GoalReached.
+            assert_eq!(events.len(), 4);
+            match &events[3] {
+                Event::GoalReached(GoalReached { fund_id: id, total }) => {
+                    assert_eq!(*id, fund_id);
+                    assert_eq!(*total, GOAL);
+                }
+                _ => panic!("expected a GoalReached event"),
+            }
+        }
+
+        #[ink::test]
+        fn fund_unknown_campaign_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, 42, 10),
+                Err(Error::UnknownCampaign)
+            );
+        }
+
+        #[ink::test]
+        fn fund_past_goal_is_rejected() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 60), Ok(()));
+            assert_eq!(
+                fund_as(&mut contract, accounts.django, fund_id, 50),
+                Err(Error::GoalExceeded)
+            );
+            assert_eq!(contract.get_funding_status(fund_id), Some(60));
+            assert_eq!(get_balance(accounts.django), 1_000);
+        }
+
+        #[ink::test]
+        fn fund_past_goal_refunds_excess() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Refund);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 60), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 50), Ok(()));
+
+            assert_eq!(contract.get_funding_status(fund_id), Some(GOAL));
+            assert_eq!(contract.get_contribution(fund_id, accounts.django), 40);
+            assert_eq!(get_balance(accounts.django), 960);
+            assert_eq!(get_balance(contract_id()), GOAL);
+        }
+
+        #[ink::test]
+        fn fund_past_goal_is_accepted() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Accept);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 80), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 50), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.eve, fund_id, 20), Ok(()));
+
+            assert_eq!(contract.get_funding_status(fund_id), Some(150));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Succeeded));
+        }
+
+        #[ink::test]
+        fn fund_after_goal_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Refund);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            assert_eq!(
+                fund_as(&mut contract, accounts.django, fund_id, 1),
+                Err(Error::GoalAlreadyReached)
+            );
+        }
+
+        #[ink::test]
+        fn fund_after_deadline_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            advance_blocks(DEADLINE + 1);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Failed));
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, fund_id, 10),
+                Err(Error::DeadlinePassed)
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_by_owner_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+
+            assert_eq!(get_balance(accounts.bob), 1_000 + GOAL);
+            assert_eq!(get_balance(contract_id()), 0);
+            assert_eq!(contract.total_escrowed(), 0);
+            assert_eq!(contract.get_funding_status(fund_id), Some(0));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Withdrawn));
+            assert!(matches!(emitted_events().last(), Some(Event::Withdrawn(_))));
+        }
+
+        #[ink::test]
+        fn withdraw_pays_beneficiary() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            set_caller(accounts.bob);
+            let fund_id = contract
+                .create_a_funding(GOAL, DEADLINE, OverflowPolicy::Reject, FundingModel::AllOrNothing, metadata(), accounts.eve, None)
+                .unwrap();
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000);
+            assert_eq!(get_balance(accounts.eve), 1_000 + GOAL);
+        }
+
+        #[ink::test]
+        fn beneficiary_handover_needs_acceptance() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.eve);
+            assert_eq!(
+                contract.propose_beneficiary(fund_id, accounts.eve),
+                Err(Error::NotCampaignOwner)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(contract.propose_beneficiary(fund_id, accounts.eve), Ok(()));
+            assert_eq!(contract.get_funding(fund_id).unwrap().beneficiary, accounts.bob);
+
+            set_caller(accounts.django);
+            assert_eq!(contract.accept_beneficiary(fund_id), Err(Error::NotPendingBeneficiary));
+            set_caller(accounts.eve);
+            assert_eq!(contract.accept_beneficiary(fund_id), Ok(()));
+            let transaction = contract.get_funding(fund_id).unwrap();
+            assert_eq!(transaction.beneficiary, accounts.eve);
+            assert_eq!(transaction.pending_beneficiary, None);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.eve), 1_000 + GOAL);
+        }
+
+        #[ink::test]
+        fn withdraw_by_non_owner_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.withdraw(fund_id), Err(Error::NotCampaignOwner));
+            assert_eq!(contract.total_escrowed(), GOAL);
+        }
+
+        #[ink::test]
+        fn withdraw_unknown_campaign_fails() {
+            let mut contract = setup();
+            assert_eq!(contract.withdraw(7), Err(Error::UnknownCampaign));
+        }
+
+        #[ink::test]
+        fn withdraw_before_goal_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 50), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Err(Error::GoalNotReached));
+        }
+
+        #[ink::test]
+        fn withdraw_twice_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(contract.withdraw(fund_id), Err(Error::AlreadyWithdrawn));
+            assert_eq!(get_balance(accounts.bob), 1_000 + GOAL);
+        }
+
+        #[ink::test]
+        fn withdraw_pays_overfunding_when_accepted() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Accept);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 150), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_150);
+        }
+
+        #[ink::test]
+        fn withdraw_only_pays_own_escrow() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let first = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let second = create_as(&mut contract, accounts.eve, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, first, GOAL), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, second, 70), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(first), Ok(()));
+
+            assert_eq!(get_balance(contract_id()), 70);
+            assert_eq!(contract.total_escrowed(), 70);
+            assert_eq!(contract.get_funding_status(second), Some(70));
+        }
+
+        #[ink::test]
+        fn withdraw_with_insufficient_contract_balance_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            set_balance(contract_id(), GOAL - 1);
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.withdraw(fund_id),
+                Err(Error::InsufficientContractBalance)
+            );
+        }
+
+        #[ink::test]
+        fn refund_after_deadline_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 20), Ok(()));
+            advance_blocks(DEADLINE + 1);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+            assert_eq!(contract.get_contribution(fund_id, accounts.charlie), 0);
+            assert_eq!(contract.get_funding_status(fund_id), Some(20));
+            assert_eq!(contract.total_escrowed(), 20);
+            assert_eq!(contract.refund(fund_id), Err(Error::NothingToRefund));
+            assert!(matches!(emitted_events().last(), Some(Event::Refunded(_))));
+        }
+
+        #[ink::test]
+        fn refund_while_open_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Err(Error::NotRefundable));
+        }
+
+        #[ink::test]
+        fn refund_of_successful_campaign_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            advance_blocks(DEADLINE + 1);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Err(Error::NotRefundable));
+        }
+
+        #[ink::test]
+        fn refund_without_contribution_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            advance_blocks(DEADLINE + 1);
+
+            set_caller(accounts.django);
+            assert_eq!(contract.refund(fund_id), Err(Error::NothingToRefund));
+        }
+
+        #[ink::test]
+        fn pause_blocks_campaigns() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.pause(), Ok(()));
+            assert!(contract.is_paused());
+            assert_eq!(
+                contract.create_a_funding(GOAL, DEADLINE, OverflowPolicy::Reject, FundingModel::AllOrNothing, metadata(), accounts.bob, None),
+                Err(Error::ContractPaused)
+            );
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, fund_id, 10),
+                Err(Error::ContractPaused)
+            );
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.unpause(), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn pause_by_non_owner_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            set_caller(accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::NotOwner));
+            assert_eq!(contract.unpause(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn cancel_campaign_allows_refunds() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.cancel_campaign(fund_id), Err(Error::NotOwner));
+            set_caller(accounts.alice);
+            assert_eq!(contract.cancel_campaign(fund_id), Ok(()));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Cancelled));
+            assert_eq!(contract.cancel_campaign(fund_id), Err(Error::CampaignCancelled));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Err(Error::CampaignCancelled));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+        }
+
+        #[ink::test]
+        fn ownership_transfer_takes_two_steps() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.propose_owner(accounts.bob), Err(Error::NotOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.propose_owner(accounts.bob), Ok(()));
+            assert_eq!(contract.pending_owner(), Some(accounts.bob));
+            assert_eq!(contract.owner(), accounts.alice);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.accept_ownership(), Err(Error::NotPendingOwner));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.accept_ownership(), Ok(()));
+            assert_eq!(contract.owner(), accounts.bob);
+            assert_eq!(contract.pending_owner(), None);
+            assert_eq!(contract.pause(), Ok(()));
+        }
+
+        #[ink::test]
+        fn withdraw_pays_platform_fee_to_treasury() {
+            let accounts = default_accounts();
+            let mut contract = setup_with_fee(FEE_BPS);
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + 95);
+            assert_eq!(get_balance(accounts.frank), 5);
+            assert_eq!(get_balance(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn fee_changes_do_not_affect_existing_campaigns() {
+            let accounts = default_accounts();
+            let mut contract = setup_with_fee(FEE_BPS);
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.set_fee(MAX_FEE_BPS), Ok(()));
+            assert_eq!(contract.fee_bps(), MAX_FEE_BPS);
+            assert_eq!(contract.get_funding(fund_id).unwrap().fee_bps, FEE_BPS);
+            let newer = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(contract.get_funding(newer).unwrap().fee_bps, MAX_FEE_BPS);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.frank), 5);
+        }
+
+        #[ink::test]
+        fn set_fee_is_capped_and_owner_only() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_fee(FEE_BPS), Err(Error::NotOwner));
+            assert_eq!(contract.set_treasury(accounts.bob), Err(Error::NotOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.set_fee(MAX_FEE_BPS + 1), Err(Error::FeeTooHigh));
+            assert_eq!(contract.set_treasury(accounts.eve), Ok(()));
+            assert_eq!(contract.treasury(), accounts.eve);
+            assert!(matches!(
+                emitted_events().last(),
+                Some(Event::TreasuryChanged(_))
+            ));
+        }
+
+        /// Create a campaign paid out in a 60 and a 40 milestone and fund it fully.
+        fn milestone_campaign(contract: &mut Fundraiser) -> FundingId {
+            let accounts = default_accounts();
+            let fund_id = create_as(contract, accounts.bob, OverflowPolicy::Reject);
+            let milestones = vec![(60, DEADLINE + 50), (40, DEADLINE + 3 * MIN_VOTING_PERIOD)];
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_milestones(fund_id, milestones, MIN_VOTING_PERIOD), Ok(()));
+            assert!(matches!(
+                emitted_events().last(),
+                Some(Event::MilestonesSet(_))
+            ));
+            assert_eq!(fund_as(contract, accounts.charlie, fund_id, 60), Ok(()));
+            assert_eq!(fund_as(contract, accounts.django, fund_id, 40), Ok(()));
+            fund_id
+        }
+
+        #[ink::test]
+        fn set_milestones_validates_input() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_milestones(
+                    fund_id,
+                    vec![(60, DEADLINE + 1), (30, DEADLINE + 2)],
+                    MIN_VOTING_PERIOD
+                ),
+                Err(Error::InvalidMilestones)
+            );
+            assert_eq!(
+                contract.set_milestones(
+                    fund_id,
+                    vec![(60, DEADLINE + 2), (40, DEADLINE + 1)],
+                    MIN_VOTING_PERIOD
+                ),
+                Err(Error::InvalidMilestones)
+            );
+            assert_eq!(
+                contract.set_milestones(fund_id, vec![(100, DEADLINE)], MIN_VOTING_PERIOD),
+                Err(Error::InvalidMilestones)
+            );
+            assert_eq!(
+                contract.set_milestones(fund_id, vec![(100, DEADLINE + 1)], MIN_VOTING_PERIOD - 1),
+                Err(Error::InvalidMilestones)
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_milestones(fund_id, vec![(100, DEADLINE + 1)], MIN_VOTING_PERIOD),
+                Err(Error::NotCampaignOwner)
+            );
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_milestones(fund_id, vec![(100, DEADLINE + 1)], MIN_VOTING_PERIOD),
+                Err(Error::MilestonesLocked)
+            );
+        }
+
+        #[ink::test]
+        fn approved_milestones_release_tranches() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = milestone_campaign(&mut contract);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Err(Error::PaysOutInMilestones));
+            assert_eq!(contract.request_milestone_release(fund_id), Ok(()));
+            assert_eq!(contract.request_milestone_release(fund_id), Err(Error::ReleaseAlreadyRequested));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.vote_milestone(fund_id, true), Ok(()));
+            assert_eq!(contract.vote_milestone(fund_id, true), Err(Error::AlreadyVoted));
+            set_caller(accounts.eve);
+            assert_eq!(contract.vote_milestone(fund_id, true), Err(Error::NotBacker));
+            assert_eq!(contract.finalize_milestone(fund_id), Err(Error::VotingOpen));
+
+            advance_blocks(MIN_VOTING_PERIOD + 1);
+            assert_eq!(contract.finalize_milestone(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + 60);
+            assert_eq!(contract.get_funding_status(fund_id), Some(40));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Succeeded));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.request_milestone_release(fund_id), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(contract.vote_milestone(fund_id, true), Ok(()));
+            advance_blocks(MIN_VOTING_PERIOD + 1);
+            assert_eq!(contract.finalize_milestone(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + GOAL);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Withdrawn));
+            assert_eq!(contract.total_escrowed(), 0);
+        }
+
+        #[ink::test]
+        fn rejected_milestone_refunds_rest_pro_rata() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = milestone_campaign(&mut contract);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.request_milestone_release(fund_id), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.vote_milestone(fund_id, true), Ok(()));
+            advance_blocks(MIN_VOTING_PERIOD + 1);
+            assert_eq!(contract.finalize_milestone(fund_id), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.request_milestone_release(fund_id), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.vote_milestone(fund_id, false), Ok(()));
+            set_caller(accounts.django);
+            assert_eq!(contract.vote_milestone(fund_id, true), Ok(()));
+            advance_blocks(MIN_VOTING_PERIOD + 1);
+            assert_eq!(contract.finalize_milestone(fund_id), Ok(()));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Failed));
+            assert_eq!(
+                contract.get_milestones(fund_id)[1].status,
+                MilestoneStatus::Rejected
+            );
+
+            // 40 are left for contributions of 100.
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000 - 60 + 24);
+            set_caller(accounts.django);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.django), 1_000 - 40 + 16);
+            assert_eq!(contract.total_escrowed(), 0);
+        }
+
+        #[ink::test]
+        fn milestone_without_quorum_is_rejected() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = milestone_campaign(&mut contract);
+
+            // Nobody votes, so nothing approves the release.
+            set_caller(accounts.bob);
+            assert_eq!(contract.request_milestone_release(fund_id), Ok(()));
+            advance_blocks(MIN_VOTING_PERIOD + 1);
+            assert_eq!(contract.finalize_milestone(fund_id), Ok(()));
+            assert_eq!(contract.get_milestones(fund_id)[0].status, MilestoneStatus::Rejected);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Failed));
+            assert_eq!(get_balance(accounts.bob), 1_000);
+
+            set_caller(accounts.django);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.django), 1_000);
+        }
+
+        #[ink::test]
+        fn missed_milestone_deadline_fails_campaign() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = milestone_campaign(&mut contract);
+
+            advance_blocks(DEADLINE + 51);
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.request_milestone_release(fund_id),
+                Err(Error::MilestoneDeadlinePassed)
+            );
+            assert_eq!(contract.finalize_milestone(fund_id), Ok(()));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Failed));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+        }
+
+        #[ink::test]
+        fn fund_tier_claims_limited_slots() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.add_reward_tier(fund_id, 20, 1, Hash::from([1; 32])),
+                Err(Error::NotCampaignOwner)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(contract.add_reward_tier(fund_id, 20, 1, Hash::from([1; 32])), Ok(0));
+            assert_eq!(contract.tier_remaining_slots(fund_id, 0), Some(1));
+            assert_eq!(contract.tier_remaining_slots(fund_id, 1), None);
+
+            assert_eq!(
+                fund_tier_as(&mut contract, accounts.charlie, fund_id, 0, 10),
+                Err(Error::PledgeBelowTierMinimum)
+            );
+            assert_eq!(
+                fund_tier_as(&mut contract, accounts.charlie, fund_id, 1, 20),
+                Err(Error::UnknownTier)
+            );
+            assert_eq!(fund_tier_as(&mut contract, accounts.charlie, fund_id, 0, 20), Ok(()));
+            assert_eq!(
+                fund_tier_as(&mut contract, accounts.charlie, fund_id, 0, 20),
+                Err(Error::TierAlreadyClaimed)
+            );
+            assert_eq!(
+                fund_tier_as(&mut contract, accounts.django, fund_id, 0, 30),
+                Err(Error::TierSoldOut)
+            );
+
+            assert_eq!(contract.claimed_tier(fund_id, accounts.charlie), Some(0));
+            assert_eq!(contract.claimed_tier(fund_id, accounts.django), None);
+            assert_eq!(contract.tier_remaining_slots(fund_id, 0), Some(0));
+            assert_eq!(contract.get_contribution(fund_id, accounts.charlie), 20);
+        }
+
+        #[ink::test]
+        fn refund_releases_tier_slot() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            set_caller(accounts.bob);
+            assert_eq!(contract.add_reward_tier(fund_id, 20, 1, Hash::from([1; 32])), Ok(0));
+            assert_eq!(fund_tier_as(&mut contract, accounts.charlie, fund_id, 0, 20), Ok(()));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.cancel_campaign(fund_id), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+
+            assert_eq!(contract.claimed_tier(fund_id, accounts.charlie), None);
+            assert_eq!(contract.tier_remaining_slots(fund_id, 0), Some(1));
+        }
+
+        #[ink::test]
+        fn keep_it_all_allows_partial_withdrawals() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_with_model(
+                &mut contract,
+                accounts.bob,
+                OverflowPolicy::Reject,
+                FundingModel::KeepItAll,
+            );
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_030);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Open));
+
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 20), Ok(()));
+            advance_blocks(DEADLINE + 1);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Succeeded));
+            set_caller(accounts.django);
+            assert_eq!(contract.refund(fund_id), Err(Error::NotRefundable));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_050);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Withdrawn));
+            assert_eq!(contract.total_escrowed(), 0);
+        }
+
+        #[ink::test]
+        fn keep_it_all_without_funding_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_with_model(
+                &mut contract,
+                accounts.bob,
+                OverflowPolicy::Reject,
+                FundingModel::KeepItAll,
+            );
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_milestones(fund_id, vec![(GOAL, DEADLINE + 1)], MIN_VOTING_PERIOD),
+                Err(Error::UnsupportedFundingModel)
+            );
+
+            advance_blocks(DEADLINE + 1);
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Failed));
+            assert_eq!(contract.withdraw(fund_id), Err(Error::GoalNotReached));
+        }
+
+        #[ink::test]
+        fn cancelled_keep_it_all_refunds_what_is_left() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_with_model(
+                &mut contract,
+                accounts.bob,
+                OverflowPolicy::Reject,
+                FundingModel::KeepItAll,
+            );
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 40), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 40), Ok(()));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.cancel_campaign(fund_id), Ok(()));
+
+            // 40 are left for contributions of 80.
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000 - 40 + 20);
+            set_caller(accounts.django);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.django), 1_000 - 40 + 20);
+        }
+
+        /// The account the PSP22 token of token campaigns lives at.
+        fn token() -> AccountId {
+            AccountId::from([0x22; 32])
+        }
+
+        /// Create a campaign denominated in `token()`.
+        fn token_campaign(contract: &mut Fundraiser, policy: OverflowPolicy) -> FundingId {
+            let accounts = default_accounts();
+            set_caller(accounts.bob);
+            contract
+                .create_a_funding(
+                    GOAL,
+                    DEADLINE,
+                    policy,
+                    FundingModel::AllOrNothing,
+                    metadata(),
+                    accounts.bob,
+                    Some(token()),
+                )
+                .expect("campaign creation failed")
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "off-chain environment does not support contract invocation")]
+        fn fund_with_token_calls_token_contract() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = token_campaign(&mut contract, OverflowPolicy::Reject);
+
+            // Moving the tokens is left to the PSP22 contract, which the off-chain
+            // environment can not call, so reaching the call is all that is checked.
+            set_caller(accounts.charlie);
+            let _ = contract.fund_with_token(fund_id, 40);
+        }
+
+        #[ink::test]
+        fn token_campaigns_reject_other_currencies() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let token_fund = token_campaign(&mut contract, OverflowPolicy::Reject);
+            let native_fund = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, token_fund, 10),
+                Err(Error::WrongCurrency)
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(contract.fund_with_token(native_fund, 10), Err(Error::WrongCurrency));
+        }
+
+        /// The storage of the first release, which is what an upgraded deployment
+        /// starts out with.
+        #[derive(SpreadLayout, SpreadAllocate)]
+        struct FundraiserV1 {
+            name: String,
+            owner: AccountId,
+            transaction_list: TransactionsV1,
+            transactions: Mapping<FundingId, TransactionV1>,
+            current_funding: Mapping<FundingId, Balance>,
+        }
+
+        #[derive(SpreadLayout, SpreadAllocate, Default)]
+        struct TransactionsV1 {
+            transactions: Vec<FundingId>,
+            next_id: FundingId,
+        }
+
+        #[derive(scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+        struct TransactionV1 {
+            callee: AccountId,
+            expected_value: Balance,
+        }
+
+        /// Store what a first release deployment of `alice` left behind after creating
+        /// `campaigns` of (creator, goal, funding, withdrawn), and load it with the
+        /// current code as if it had just been upgraded.
+        fn deploy_v1(campaigns: &[(AccountId, Balance, Balance, bool)]) -> Fundraiser {
+            let root_key = <Fundraiser as ink_lang::codegen::ContractRootKey>::ROOT_KEY;
+            let mut v1 = FundraiserV1::allocate_spread(&mut ink_primitives::KeyPtr::from(root_key));
+            v1.name = String::from("fundraiser");
+            v1.owner = default_accounts().alice;
+            let mut escrowed = 0;
+            for (fund_id, &(creator, goal, funding, withdrawn)) in (0..).zip(campaigns) {
+                if !withdrawn {
+                    v1.transactions.insert(fund_id, &TransactionV1 {
+                        callee: creator,
+                        expected_value: goal,
+                    });
+                    escrowed += funding;
+                }
+                if funding > 0 {
+                    v1.current_funding.insert(fund_id, &funding);
+                }
+                v1.transaction_list.transactions.push(fund_id);
+                v1.transaction_list.next_id = fund_id + 1;
+            }
+            ink_storage::traits::push_spread_root(&v1, &root_key);
+
+            let accounts = default_accounts();
+            for account in [accounts.bob, accounts.charlie, accounts.django, accounts.eve] {
+                set_balance(account, 1_000);
+            }
+            set_balance(contract_id(), escrowed);
+            set_caller(accounts.alice);
+            ink_storage::traits::pull_spread_root(&root_key)
+        }
+
+        #[ink::test]
+        fn upgrade_by_non_owner_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            set_caller(accounts.bob);
+            assert_eq!(contract.upgrade([0x01; 32]), Err(Error::NotOwner));
+            assert_eq!(contract.migrate(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn migrate_from_first_release_keeps_every_balance() {
+            let accounts = default_accounts();
+            let mut contract = deploy_v1(&[
+                (accounts.bob, GOAL, GOAL, true),
+                (accounts.charlie, GOAL, GOAL, false),
+                (accounts.bob, GOAL, 30, false),
+                (accounts.bob, GOAL, 0, false),
+            ]);
+            assert_eq!(contract.storage_version(), 1);
+            assert_eq!(contract.name(), "fundraiser");
+            assert_eq!(fund_as(&mut contract, accounts.eve, 2, 5), Err(Error::MigrationPending));
+            set_caller(accounts.eve);
+            assert_eq!(contract.refund(2), Err(Error::MigrationPending));
+
+            set_caller(accounts.alice);
+            assert_eq!(contract.migrate(), Ok(true));
+            assert_eq!(contract.storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.migrate(), Err(Error::NothingToMigrate));
+            assert!(emitted_events().iter().any(|event| matches!(event, Event::Migrated(_))));
+
+            assert_eq!(contract.campaign_count(), 4);
+            assert!(contract.get_funding(0).is_none());
+            assert_eq!(contract.get_funding_status(0), None);
+            assert_eq!(contract.get_funding_status(1), Some(GOAL));
+            assert_eq!(contract.total_escrowed(), GOAL + 30);
+            assert_eq!(contract.treasury(), accounts.alice);
+            let campaign = contract.get_funding(2).expect("campaign was migrated");
+            assert_eq!(campaign.callee, accounts.bob);
+            assert_eq!(campaign.beneficiary, accounts.bob);
+            assert_eq!(campaign.expected_value, GOAL);
+            let by_bob: Vec<FundingId> = contract
+                .campaigns_by_creator(accounts.bob, 0, 10)
+                .into_iter()
+                .map(|(fund_id, _)| fund_id)
+                .collect();
+            assert_eq!(by_bob, [2, 3]);
+
+            // Nobody could be refunded, so the carried over campaigns stay open.
+            assert_eq!(contract.cancel_campaign(2), Err(Error::LegacyCampaign));
+            set_caller(accounts.bob);
+            assert_eq!(contract.cancel_funding(3), Err(Error::LegacyCampaign));
+            assert_eq!(contract.get_campaign_state(2), Some(CampaignState::Open));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.withdraw(1), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000 + GOAL);
+            assert_eq!(fund_as(&mut contract, accounts.eve, 2, GOAL - 30), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(2), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + GOAL);
+            assert_eq!(contract.total_escrowed(), 0);
+            assert_eq!(get_balance(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn migrate_runs_in_batches() {
+            let accounts = default_accounts();
+            let campaigns = [(accounts.bob, GOAL, 1, false); MIGRATION_BATCH_SIZE as usize + 1];
+            let mut contract = deploy_v1(&campaigns);
+
+            assert_eq!(contract.migrate(), Ok(false));
+            assert_eq!(contract.storage_version(), 1);
+            assert_eq!(contract.total_escrowed(), Balance::from(MIGRATION_BATCH_SIZE));
+            assert_eq!(contract.migrate(), Ok(true));
+            assert_eq!(contract.storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.total_escrowed(), Balance::from(MIGRATION_BATCH_SIZE) + 1);
+            assert_eq!(contract.campaigns_by_creator(accounts.bob, 0, MAX_PAGE_SIZE).len(), MAX_PAGE_SIZE as usize);
+        }
+
+        /// Create a campaign as `creator` with `deposit` put down.
+        fn create_with_deposit(contract: &mut Fundraiser, creator: AccountId, deposit: Balance) -> FundingId {
+            call_with_value(creator, deposit, || {
+                contract.create_a_funding(
+                    GOAL,
+                    DEADLINE,
+                    OverflowPolicy::Reject,
+                    FundingModel::AllOrNothing,
+                    metadata(),
+                    creator,
+                    None,
+                )
+            })
+            .expect("campaign creation failed")
+        }
+
+        #[ink::test]
+        fn cancel_funding_refunds_backers_and_charges_penalty() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            assert_eq!(contract.set_cancellation_penalty(1_000), Ok(()));
+            let fund_id = create_with_deposit(&mut contract, accounts.bob, 50);
+            assert_eq!(
+                contract.get_deposit(fund_id),
+                Some(Deposit { amount: 50, penalty_bps: 1_000 })
+            );
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 40), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 20), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.cancel_funding(fund_id), Err(Error::NotCampaignOwner));
+            set_caller(accounts.bob);
+            assert_eq!(contract.cancel_funding(fund_id), Ok(()));
+            assert_eq!(contract.get_campaign_state(fund_id), Some(CampaignState::Cancelled));
+            assert_eq!(get_balance(accounts.bob), 1_000 - 5);
+            assert_eq!(get_balance(accounts.frank), 5);
+            assert_eq!(contract.get_deposit(fund_id), None);
+            assert_eq!(contract.cancel_funding(fund_id), Err(Error::CampaignCancelled));
+
+            assert_eq!(
+                fund_as(&mut contract, accounts.eve, fund_id, 10),
+                Err(Error::CampaignCancelled)
+            );
+            for backer in [accounts.charlie, accounts.django] {
+                set_caller(backer);
+                assert_eq!(contract.refund(fund_id), Ok(()));
+                assert_eq!(get_balance(backer), 1_000);
+            }
+            assert_eq!(get_balance(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn cancel_funding_after_goal_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_with_deposit(&mut contract, accounts.bob, 50);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.cancel_funding(fund_id), Err(Error::GoalAlreadyReached));
+            assert_eq!(contract.reclaim_deposit(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000);
+            assert_eq!(contract.reclaim_deposit(fund_id), Err(Error::NoDeposit));
+        }
+
+        #[ink::test]
+        fn reclaim_deposit_of_open_campaign_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_with_deposit(&mut contract, accounts.bob, 50);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.reclaim_deposit(fund_id), Err(Error::CampaignOpen));
+            advance_blocks(DEADLINE + 1);
+            assert_eq!(contract.reclaim_deposit(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000);
+        }
+
+        #[ink::test]
+        fn set_cancellation_penalty_above_deposit_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            assert_eq!(contract.set_cancellation_penalty(10_001), Err(Error::PenaltyTooHigh));
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_cancellation_penalty(100), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn zero_contribution_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, fund_id, 0),
+                Err(Error::ZeroContribution)
+            );
+            assert_eq!(contract.backer_count(fund_id), 0);
+        }
+
+        #[ink::test]
+        fn contribution_limits_are_enforced() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let limits = ContributionLimits {
+                min_contribution: Some(10),
+                max_per_backer: Some(40),
+                max_backers: Some(2),
+            };
+            set_caller(accounts.charlie);
+            assert_eq!(contract.set_contribution_limits(fund_id, limits), Err(Error::NotCampaignOwner));
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_contribution_limits(fund_id, limits), Ok(()));
+            assert_eq!(contract.contribution_limits(fund_id), limits);
+
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, fund_id, 9),
+                Err(Error::ContributionTooSmall)
+            );
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+            assert_eq!(
+                fund_as(&mut contract, accounts.charlie, fund_id, 11),
+                Err(Error::BackerCapExceeded)
+            );
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 20), Ok(()));
+            assert_eq!(
+                fund_as(&mut contract, accounts.eve, fund_id, 20),
+                Err(Error::TooManyBackers)
+            );
+            assert_eq!(contract.backer_count(fund_id), 2);
+            assert_eq!(get_balance(accounts.eve), 1_000);
+
+            // Returning backers are not counted twice.
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 10), Ok(()));
+            assert_eq!(contract.backer_count(fund_id), 2);
+        }
+
+        #[ink::test]
+        fn set_contribution_limits_with_min_above_cap_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let limits = ContributionLimits {
+                min_contribution: Some(50),
+                max_per_backer: Some(40),
+                max_backers: None,
+            };
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_contribution_limits(fund_id, limits), Err(Error::InvalidLimits));
+            assert_eq!(contract.contribution_limits(fund_id), ContributionLimits::default());
+        }
+
+        #[ink::test]
+        fn allowlist_restricts_backers() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.set_access(fund_id, CampaignAccess::Allowlist), Err(Error::NotCampaignOwner));
+            assert_eq!(
+                contract.add_to_allowlist(fund_id, Vec::from([accounts.charlie])),
+                Err(Error::NotCampaignOwner)
+            );
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_access(fund_id, CampaignAccess::Allowlist), Ok(()));
+            assert_eq!(
+                contract.add_to_allowlist(fund_id, Vec::from([accounts.charlie, accounts.django])),
+                Ok(())
+            );
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.eve, fund_id, 10), Err(Error::NotAllowed));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.remove_from_allowlist(fund_id, Vec::from([accounts.django])), Ok(()));
+            assert!(matches!(
+                emitted_events().last(),
+                Some(Event::AllowlistUpdated(AllowlistUpdated { allowed: false, .. }))
+            ));
+            assert!(!contract.is_allowed(fund_id, accounts.django));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 10), Err(Error::NotAllowed));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_access(fund_id, CampaignAccess::Public), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.eve, fund_id, 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn allowlist_batch_is_bounded() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.bob);
+            let batch = Vec::from([accounts.charlie; MAX_ALLOWLIST_BATCH + 1]);
+            assert_eq!(contract.add_to_allowlist(fund_id, batch), Err(Error::BatchTooLarge));
+        }
+
+        #[ink::test]
+        fn fund_with_proof_works() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            // A tree over charlie, django, eve and frank.
+            let leaves: Vec<Hash> = [accounts.charlie, accounts.django, accounts.eve, accounts.frank]
+                .iter()
+                .map(Fundraiser::merkle_leaf)
+                .collect();
+            let left = Fundraiser::merkle_root(leaves[0], &[leaves[1]]);
+            let right = Fundraiser::merkle_root(leaves[2], &[leaves[3]]);
+            let root = Fundraiser::merkle_root(left, &[right]);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_access(fund_id, CampaignAccess::MerkleRoot(root)), Ok(()));
+
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 10), Err(Error::NotAllowed));
+            let proof = Vec::from([leaves[0], right]);
+            assert_eq!(
+                call_with_value(accounts.django, 10, || contract.fund_with_proof(fund_id, proof)),
+                Ok(())
+            );
+            assert_eq!(contract.get_contribution(fund_id, accounts.django), 10);
+            // Once proven, the plain `fund` works as well.
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 10), Ok(()));
+
+            let proof = Vec::from([leaves[3], left]);
+            assert_eq!(
+                call_with_value(accounts.charlie, 10, || contract.fund_with_proof(fund_id, proof)),
+                Err(Error::InvalidProof)
+            );
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+        }
+
+        /// Set a pool of `amount` aside as `eve`, matching 1:1 up to 30 per campaign,
+        /// and attach it to `fund_id`.
+        fn sponsor_campaign(contract: &mut Fundraiser, fund_id: FundingId, amount: Balance) -> PoolId {
+            let accounts = default_accounts();
+            let pool_id = call_with_value(accounts.eve, amount, || contract.create_sponsor_pool(10_000, 30))
+                .expect("pool creation failed");
+            assert_eq!(contract.attach_pool(pool_id, fund_id), Ok(()));
+            pool_id
+        }
+
+        #[ink::test]
+        fn sponsor_pool_matches_up_to_cap() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let pool_id = sponsor_campaign(&mut contract, fund_id, 50);
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 20), Ok(()));
+            assert_eq!(contract.get_funding_status(fund_id), Some(40));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 20), Ok(()));
+            assert_eq!(contract.get_funding_status(fund_id), Some(70));
+            assert_eq!(contract.matched_amount(fund_id), 30);
+            assert_eq!(contract.get_sponsor_pool(pool_id).map(|pool| pool.available), Some(20));
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+            assert_eq!(
+                contract.get_campaign_state(fund_id),
+                Some(CampaignState::Succeeded)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + GOAL);
+
+            set_caller(accounts.eve);
+            assert_eq!(contract.reclaim_pool(pool_id), Ok(()));
+            assert_eq!(get_balance(accounts.eve), 1_000 - 30);
+            assert_eq!(contract.reclaim_pool(pool_id), Err(Error::NothingToReclaim));
+        }
+
+        #[ink::test]
+        fn sponsor_pool_of_failed_campaign_is_reclaimable() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let pool_id = sponsor_campaign(&mut contract, fund_id, 50);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 20), Ok(()));
+
+            set_caller(accounts.eve);
+            assert_eq!(contract.reclaim_pool(pool_id), Err(Error::PoolCampaignsOpen));
+
+            advance_blocks(DEADLINE + 1);
+            assert_eq!(contract.reclaim_pool(pool_id), Ok(()));
+            assert_eq!(get_balance(accounts.eve), 1_000);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+            assert_eq!(get_balance(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn attach_pool_by_non_sponsor_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let pool_id = sponsor_campaign(&mut contract, fund_id, 50);
+            let other = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.attach_pool(pool_id, other), Err(Error::NotSponsor));
+            set_caller(accounts.eve);
+            assert_eq!(contract.attach_pool(pool_id, fund_id), Err(Error::PoolAlreadyAttached));
+            assert_eq!(contract.attach_pool(1, other), Err(Error::UnknownPool));
+        }
+
+        #[ink::test]
+        fn isqrt_works() {
+            for (n, root) in [(0, 0), (1, 1), (2, 1), (3, 1), (4, 2), (15, 3), (16, 4), (17, 4)] {
+                assert_eq!(Fundraiser::isqrt(n), root);
+            }
+            assert_eq!(Fundraiser::isqrt(10u128.pow(20)), 10u128.pow(10));
+            assert_eq!(Fundraiser::isqrt(u128::MAX), u128::from(u64::MAX));
+        }
+
+        #[ink::test]
+        fn mul_div_works() {
+            assert_eq!(Fundraiser::mul_div(7, 6, 4), Some(10));
+            assert_eq!(Fundraiser::mul_div(7, 6, 0), None);
+            assert_eq!(Fundraiser::mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+            assert_eq!(Fundraiser::mul_div(u128::MAX, 3, 4), Some(u128::MAX / 4 * 3 + 2));
+            assert_eq!(Fundraiser::mul_div(u128::MAX, 2, 1), None);
+            let big = 10u128.pow(24);
+            assert_eq!(Fundraiser::mul_div(big, 4 * big, 8 * big), Some(big / 2));
+        }
+
+        /// Open a round over blocks 0 to 5 with a pool of 100 from `eve`, and enter
+        /// two campaigns of `bob` into it.
+        fn round_with_two_campaigns(contract: &mut Fundraiser) -> (RoundId, FundingId, FundingId) {
+            let accounts = default_accounts();
+            let round_id = call_with_value(accounts.eve, 100, || contract.create_round(0, 5))
+                .expect("round creation failed");
+            let first = create_as(contract, accounts.bob, OverflowPolicy::Reject);
+            let second = create_as(contract, accounts.bob, OverflowPolicy::Reject);
+            set_caller(accounts.bob);
+            assert_eq!(contract.join_round(round_id, first), Ok(()));
+            assert_eq!(contract.join_round(round_id, second), Ok(()));
+            (round_id, first, second)
+        }
+
+        #[ink::test]
+        fn finalize_round_splits_pool_quadratically() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let (round_id, first, second) = round_with_two_campaigns(&mut contract);
+
+            // Two backers of 4 each score (2 + 2)^2 = 16, one backer of 9 scores 3^2 = 9.
+            assert_eq!(fund_as(&mut contract, accounts.charlie, first, 4), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, first, 4), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.charlie, second, 9), Ok(()));
+            assert_eq!(contract.round_contribution(first, accounts.charlie), 4);
+
+            assert_eq!(contract.finalize_round(round_id), Err(Error::RoundNotEnded));
+            advance_blocks(6);
+            // Contributions after the round has ended do not count.
+            assert_eq!(fund_as(&mut contract, accounts.django, second, 20), Ok(()));
+            assert_eq!(contract.round_contribution(second, accounts.django), 0);
+
+            assert_eq!(contract.finalize_round(round_id), Ok(()));
+            assert_eq!(contract.round_match(first), 64);
+            assert_eq!(contract.round_match(second), 36);
+            assert_eq!(contract.get_funding_status(first), Some(8 + 64));
+            assert_eq!(contract.get_funding_status(second), Some(29 + 36));
+            assert_eq!(get_balance(accounts.eve), 900);
+            assert_eq!(contract.finalize_round(round_id), Err(Error::RoundFinalized));
+        }
+
+        #[ink::test]
+        fn round_match_stops_at_funding_ceiling() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let (round_id, first, second) = round_with_two_campaigns(&mut contract);
+
+            // Scores of 4^2 = 16 and 9^2 = 81 would match the second with 83, but it
+            // only has room for 19 more.
+            assert_eq!(fund_as(&mut contract, accounts.charlie, first, 4), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, first, 4), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.charlie, second, 81), Ok(()));
+
+            advance_blocks(6);
+            assert_eq!(contract.finalize_round(round_id), Ok(()));
+            assert_eq!(contract.round_match(first), 16);
+            assert_eq!(contract.round_match(second), 19);
+            assert_eq!(contract.get_funding_status(second), Some(GOAL));
+            assert_eq!(get_balance(accounts.eve), 1_000 - 16 - 19);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(second), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + GOAL);
+            assert_eq!(get_balance(contract_id()), 8 + 16);
+            assert_eq!(contract.total_escrowed(), 8 + 16);
+        }
+
+        #[ink::test]
+        fn finalize_round_splits_pools_of_token_scale() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            // One unit with 18 decimals, a million of them in the pool.
+            let unit = 10u128.pow(18);
+            set_balance(accounts.eve, 1_000_000 * unit);
+            let round_id = call_with_value(accounts.eve, 1_000_000 * unit, || contract.create_round(0, 5))
+                .expect("round creation failed");
+            let first = create_as(&mut contract, accounts.bob, OverflowPolicy::Accept);
+            let second = create_as(&mut contract, accounts.bob, OverflowPolicy::Accept);
+            set_caller(accounts.bob);
+            assert_eq!(contract.join_round(round_id, first), Ok(()));
+            assert_eq!(contract.join_round(round_id, second), Ok(()));
+
+            // Two backers of a million each score as much as one backer of four million.
+            for (backer, fund_id, amount) in [
+                (accounts.charlie, first, 1_000_000 * unit),
+                (accounts.django, first, 1_000_000 * unit),
+                (accounts.frank, second, 4_000_000 * unit),
+            ] {
+                set_balance(backer, amount);
+                assert_eq!(fund_as(&mut contract, backer, fund_id, amount), Ok(()));
+            }
+
+            advance_blocks(6);
+            assert_eq!(contract.finalize_round(round_id), Ok(()));
+            assert_eq!(contract.round_match(first), 500_000 * unit);
+            assert_eq!(contract.round_match(second), 500_000 * unit);
+            assert_eq!(get_balance(accounts.eve), 0);
+        }
+
+        #[ink::test]
+        fn finalize_round_pays_pool_back_when_scores_overflow() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let round_id = call_with_value(accounts.eve, 100, || contract.create_round(0, 5))
+                .expect("round creation failed");
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Accept);
+            set_caller(accounts.bob);
+            assert_eq!(contract.join_round(round_id, fund_id), Ok(()));
+
+            // Two backers of a third of the range each score four thirds of it.
+            let amount = Balance::MAX / 3;
+            for backer in [accounts.charlie, accounts.django] {
+                set_balance(backer, amount);
+                assert_eq!(fund_as(&mut contract, backer, fund_id, amount), Ok(()));
+            }
+
+            advance_blocks(6);
+            assert_eq!(contract.finalize_round(round_id), Ok(()));
+            assert_eq!(contract.round_match(fund_id), 0);
+            assert_eq!(get_balance(accounts.eve), 1_000);
+            assert!(contract.get_round(round_id).expect("round exists").finalized);
+            assert_eq!(contract.finalize_round(round_id), Err(Error::RoundFinalized));
+        }
+
+        #[ink::test]
+        fn round_matches_of_failed_campaigns_are_reclaimable() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let (round_id, first, second) = round_with_two_campaigns(&mut contract);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, first, 4), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, first, 4), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.charlie, second, 9), Ok(()));
+
+            advance_blocks(6);
+            assert_eq!(contract.finalize_round(round_id), Ok(()));
+            set_caller(accounts.eve);
+            assert_eq!(contract.reclaim_round(round_id), Err(Error::PoolCampaignsOpen));
+
+            advance_blocks(DEADLINE);
+            set_caller(accounts.bob);
+            assert_eq!(contract.reclaim_round(round_id), Err(Error::NotRoundOperator));
+            set_caller(accounts.eve);
+            assert_eq!(contract.reclaim_round(round_id), Ok(()));
+            assert_eq!(get_balance(accounts.eve), 1_000);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(first), Ok(()));
+            assert_eq!(contract.refund(second), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+        }
+
+        #[ink::test]
+        fn join_round_after_end_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let round_id = call_with_value(accounts.eve, 100, || contract.create_round(0, 5))
+                .expect("round creation failed");
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.join_round(round_id, fund_id), Err(Error::NotCampaignOwner));
+            advance_blocks(6);
+            set_caller(accounts.bob);
+            assert_eq!(contract.join_round(round_id, fund_id), Err(Error::RoundEnded));
+        }
+
+        #[ink::test]
+        fn stretch_goals_keep_campaign_open_up_to_hard_cap() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Refund);
+            let goals = Vec::from([(150, Hash::from([0x01; 32])), (200, Hash::from([0x02; 32]))]);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_stretch_goals(fund_id, goals, 250), Ok(()));
+            assert_eq!(contract.hard_cap(fund_id), Some(250));
+            assert!(matches!(
+                emitted_events().last(),
+                Some(Event::StretchGoalsSet(StretchGoalsSet { hard_cap: Some(250), .. }))
+            ));
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            assert_eq!(contract.stretch_level(fund_id), 0);
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 120), Ok(()));
+            assert_eq!(contract.stretch_level(fund_id), 2);
+            let unlocked: Vec<u32> = emitted_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::StretchGoalUnlocked(unlocked) => Some(unlocked.level),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(unlocked, [1, 2]);
+
+            // The hard cap is handled as the overflow policy says.
+            assert_eq!(fund_as(&mut contract, accounts.eve, fund_id, 50), Ok(()));
+            assert_eq!(get_balance(accounts.eve), 1_000 - 30);
+            assert_eq!(contract.get_funding_status(fund_id), Some(250));
+            assert_eq!(
+                fund_as(&mut contract, accounts.eve, fund_id, 10),
+                Err(Error::HardCapReached)
+            );
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + 250);
+        }
+
+        #[ink::test]
+        fn set_stretch_goals_after_goal_reopens_campaign() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let goals = Vec::from([(150, Hash::from([0x01; 32]))]);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+            assert_eq!(
+                fund_as(&mut contract, accounts.django, fund_id, 10),
+                Err(Error::GoalAlreadyReached)
+            );
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_stretch_goals(fund_id, goals.clone(), 150), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 50), Ok(()));
+            assert_eq!(contract.stretch_level(fund_id), 1);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(
+                contract.set_stretch_goals(fund_id, goals, 200),
+                Err(Error::AlreadyWithdrawn)
+            );
+        }
+
+        #[ink::test]
+        fn removing_stretch_goals_past_goal_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let goals = Vec::from([(150, Hash::from([0x01; 32]))]);
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_stretch_goals(fund_id, goals, 150), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 120), Ok(()));
+
+            // Dropping the cap would leave the campaign holding more than its goal.
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_stretch_goals(fund_id, Vec::new(), 0),
+                Err(Error::InvalidStretchGoals)
+            );
+            assert_eq!(contract.hard_cap(fund_id), Some(150));
+
+            assert_eq!(contract.withdraw(fund_id), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 + 120);
+            assert_eq!(contract.get_funding_status(fund_id), Some(0));
+            assert_eq!(get_balance(contract_id()), 0);
+        }
+
+        #[ink::test]
+        fn set_stretch_goals_validates_amounts() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let hash = Hash::from([0x01; 32]);
+
+            set_caller(accounts.bob);
+            for (goals, hard_cap) in [
+                (Vec::from([(GOAL, hash)]), 200),
+                (Vec::from([(180, hash), (150, hash)]), 200),
+                (Vec::from([(150, hash)]), 120),
+                (Vec::new(), GOAL - 1),
+            ] {
+                assert_eq!(
+                    contract.set_stretch_goals(fund_id, goals, hard_cap),
+                    Err(Error::InvalidStretchGoals)
+                );
+            }
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_stretch_goals(fund_id, Vec::from([(150, hash)]), 200),
+                Err(Error::NotCampaignOwner)
+            );
+        }
+
+        #[ink::test]
+        fn unfund_keeps_totals_and_tiers_consistent() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            set_caller(accounts.bob);
+            assert_eq!(contract.add_reward_tier(fund_id, 50, 1, Hash::from([0x01; 32])), Ok(0));
+            assert_eq!(fund_tier_as(&mut contract, accounts.charlie, fund_id, 0, 60), Ok(()));
+            assert_eq!(fund_as(&mut contract, accounts.django, fund_id, 10), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.unfund(fund_id, 61), Err(Error::AmountExceedsContribution));
+            assert_eq!(contract.unfund(fund_id, 20), Ok(()));
+            assert_eq!(contract.get_contribution(fund_id, accounts.charlie), 40);
+            assert_eq!(contract.get_funding_status(fund_id), Some(50));
+            assert_eq!(get_balance(accounts.charlie), 1_000 - 40);
+            // 40 is below the tier's minimum, so the slot is free again.
+            assert_eq!(contract.claimed_tier(fund_id, accounts.charlie), None);
+            assert_eq!(contract.tier_remaining_slots(fund_id, 0), Some(1));
+
+            assert_eq!(contract.unfund(fund_id, 40), Ok(()));
+            assert_eq!(contract.get_contribution(fund_id, accounts.charlie), 0);
+            assert_eq!(contract.backer_count(fund_id), 1);
+            assert_eq!(contract.total_escrowed(), 10);
+            assert_eq!(get_balance(accounts.charlie), 1_000);
+            assert_eq!(contract.unfund(fund_id, 1), Err(Error::NothingToRefund));
+        }
+
+        #[ink::test]
+        fn unfund_after_goal_fails() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, GOAL), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.unfund(fund_id, 10), Err(Error::GoalAlreadyReached));
+        }
+
+        #[ink::test]
+        fn retraction_policy_is_enforced() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 30), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_retraction_policy(fund_id, RetractionPolicy::Disabled),
+                Err(Error::NotCampaignOwner)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_retraction_policy(fund_id, RetractionPolicy::Disabled), Ok(()));
+            assert!(matches!(
+                emitted_events().last(),
+                Some(Event::RetractionPolicyChanged(RetractionPolicyChanged {
+                    policy: RetractionPolicy::Disabled,
+                    ..
+                }))
+            ));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.unfund(fund_id, 10), Err(Error::RetractionDisabled));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_retraction_policy(fund_id, RetractionPolicy::CutOff(5)), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(contract.unfund(fund_id, 10), Ok(()));
+            advance_blocks(DEADLINE - 4);
+            assert_eq!(contract.unfund(fund_id, 10), Err(Error::RetractionClosed));
+        }
+
+        #[ink::test]
+        fn unfund_hands_match_back_to_pool() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            let pool_id = sponsor_campaign(&mut contract, fund_id, 50);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 20), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.unfund(fund_id, 10), Ok(()));
+            assert_eq!(contract.matched_amount(fund_id), 10);
+            assert_eq!(contract.get_funding_status(fund_id), Some(20));
+            assert_eq!(contract.get_sponsor_pool(pool_id).map(|pool| pool.available), Some(40));
+        }
+
+        #[ink::test]
+        fn fund_mints_and_updates_receipt() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            set_caller(accounts.bob);
+            assert_eq!(contract.add_reward_tier(fund_id, 20, 5, Hash::from([0x01; 32])), Ok(0));
+
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            let id = contract.receipt_of(fund_id, accounts.charlie).expect("receipt was minted");
+            assert_eq!(contract.owner_of(id.clone()), Some(accounts.charlie));
+            assert_eq!(contract.balance_of(accounts.charlie), 1);
+            assert_eq!(contract.total_supply(), 1);
+
+            assert_eq!(fund_tier_as(&mut contract, accounts.charlie, fund_id, 0, 20), Ok(()));
+            assert_eq!(contract.receipt_of(fund_id, accounts.charlie), Some(id.clone()));
+            assert_eq!(
+                contract.get_receipt(id),
+                Some(Receipt {
+                    fund_id,
+                    amount: 30,
+                    tier_id: Some(0),
+                })
+            );
+            assert_eq!(contract.total_supply(), 1);
+        }
+
+        #[ink::test]
+        fn refund_burns_receipt() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            let id = contract.receipt_of(fund_id, accounts.charlie).expect("receipt was minted");
+
+            advance_blocks(DEADLINE + 1);
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(contract.owner_of(id.clone()), None);
+            assert_eq!(contract.get_receipt(id), None);
+            assert_eq!(contract.receipt_of(fund_id, accounts.charlie), None);
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            assert_eq!(contract.total_supply(), 0);
+            assert!(matches!(
+                emitted_events().last(),
+                Some(Event::Refunded(_))
+            ));
+        }
+
+        #[ink::test]
+        fn receipt_transfer_needs_approval() {
+            let accounts = default_accounts();
+            let mut contract = setup();
+            let fund_id = create_as(&mut contract, accounts.bob, OverflowPolicy::Reject);
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            let id = contract.receipt_of(fund_id, accounts.charlie).expect("receipt was minted");
+
+            set_caller(accounts.django);
+            assert_eq!(
+                contract.transfer(accounts.django, id.clone(), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+            set_caller(accounts.charlie);
+            assert_eq!(contract.approve(accounts.django, Some(id.clone()), true), Ok(()));
+            assert!(contract.allowance(accounts.charlie, accounts.django, Some(id.clone())));
+
+            set_caller(accounts.django);
+            assert_eq!(contract.transfer(accounts.eve, id.clone(), Vec::new()), Ok(()));
+            assert_eq!(contract.owner_of(id.clone()), Some(accounts.eve));
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            assert_eq!(contract.balance_of(accounts.eve), 1);
+            assert!(!contract.allowance(accounts.charlie, accounts.django, Some(id.clone())));
+
+            // Funding again updates the receipt eve holds rather than minting another.
+            assert_eq!(fund_as(&mut contract, accounts.charlie, fund_id, 10), Ok(()));
+            assert_eq!(contract.receipt_of(fund_id, accounts.charlie), Some(id.clone()));
+            assert_eq!(contract.get_receipt(id.clone()).map(|receipt| receipt.amount), Some(20));
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            assert_eq!(contract.total_supply(), 1);
 
-        /// Panic if the current funding is full
-        fn ensure_funding_is_full(&self, fund_id: FundingId) {
-            let transaction = self.transactions.get(fund_id).unwrap();
-            let current_funding = self.current_funding.get(fund_id).unwrap();
-            assert!(current_funding >= transaction.expected_value);
+            // The refund burns it all the same.
+            advance_blocks(DEADLINE + 1);
+            set_caller(accounts.charlie);
+            assert_eq!(contract.refund(fund_id), Ok(()));
+            assert_eq!(contract.owner_of(id.clone()), None);
+            assert_eq!(contract.get_receipt(id), None);
+            assert_eq!(contract.balance_of(accounts.eve), 0);
+            assert_eq!(contract.total_supply(), 0);
         }
     }
 }
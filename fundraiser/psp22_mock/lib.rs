@@ -0,0 +1,150 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A minimal PSP22 token used to test token-denominated campaigns.
+//!
+//! It only implements the messages the fundraiser calls, under their PSP22
+//! selectors, and lets anyone mint.
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod psp22_mock {
+    use ink_prelude::{string::String, vec::Vec};
+    use ink_storage::{traits::SpreadAllocate, Mapping};
+
+    /// Errors as laid out by the PSP22 standard.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate, Default)]
+    pub struct Psp22Mock {
+        total_supply: Balance,
+        balances: Mapping<AccountId, Balance>,
+        allowances: Mapping<(AccountId, AccountId), Balance>,
+    }
+
+    impl Psp22Mock {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            ink_lang::utils::initialize_contract(|_: &mut Self| {})
+        }
+
+        #[ink(message, selector = 0x162df8c2)]
+        pub fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        #[ink(message, selector = 0x6568382f)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        #[ink(message, selector = 0x4d47d921)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((owner, spender)).unwrap_or(0)
+        }
+
+        #[ink(message, selector = 0xdb20f9f5)]
+        pub fn transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> Result<(), PSP22Error> {
+            let from = self.env().caller();
+            self.transfer_from_to(from, to, value)
+        }
+
+        #[ink(message, selector = 0x54b3c76e)]
+        pub fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            let spender = self.env().caller();
+            let allowance = self.allowance(from, spender);
+            if allowance < value {
+                return Err(PSP22Error::InsufficientAllowance)
+            }
+            self.transfer_from_to(from, to, value)?;
+            self.allowances.insert((from, spender), &(allowance - value));
+            Ok(())
+        }
+
+        #[ink(message, selector = 0xb20f1bbd)]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            self.allowances.insert((owner, spender), &value);
+            Ok(())
+        }
+
+        /// Create `value` new tokens for `to`. Not part of PSP22.
+        #[ink(message)]
+        pub fn mint(&mut self, to: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            self.balances.insert(to, &(self.balance_of(to) + value));
+            self.total_supply += value;
+            Ok(())
+        }
+
+        fn transfer_from_to(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let from_balance = self.balance_of(from);
+            if from_balance < value {
+                return Err(PSP22Error::InsufficientBalance)
+            }
+            self.balances.insert(from, &(from_balance - value));
+            self.balances.insert(to, &(self.balance_of(to) + value));
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(caller);
+        }
+
+        #[ink::test]
+        fn transfer_moves_balance() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut token = Psp22Mock::new();
+            assert_eq!(token.mint(accounts.bob, 100), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(token.transfer(accounts.charlie, 60, Vec::new()), Ok(()));
+            assert_eq!(token.balance_of(accounts.bob), 40);
+            assert_eq!(token.balance_of(accounts.charlie), 60);
+            assert_eq!(
+                token.transfer(accounts.charlie, 41, Vec::new()),
+                Err(PSP22Error::InsufficientBalance)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_from_needs_allowance() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut token = Psp22Mock::new();
+            assert_eq!(token.mint(accounts.bob, 100), Ok(()));
+
+            set_caller(accounts.bob);
+            assert_eq!(token.approve(accounts.charlie, 30), Ok(()));
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                token.transfer_from(accounts.bob, accounts.django, 31, Vec::new()),
+                Err(PSP22Error::InsufficientAllowance)
+            );
+            assert_eq!(token.transfer_from(accounts.bob, accounts.django, 30, Vec::new()), Ok(()));
+            assert_eq!(token.balance_of(accounts.django), 30);
+            assert_eq!(token.allowance(accounts.bob, accounts.charlie), 0);
+        }
+    }
+}